@@ -0,0 +1,393 @@
+// Libs
+use std::{
+    env::var,
+    fs,
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::database::DatabaseConfig;
+use crate::limiter::LimiterConfig;
+use crate::logger::LogConfig;
+use crate::migrations::MigrationMode;
+use crate::security::SecurityConfig;
+use crate::telemetry::TelemetryConfig;
+use crate::trash::TrashConfig;
+use crate::validation::{DESCRIPTION, TITLE, USERNAME};
+
+// Data
+static CONFIG: OnceCell<Config> = OnceCell::new();
+static REDACTED: &str = "********";
+
+// Enums
+#[derive(Clone, Debug, Subcommand)]
+pub enum Command {
+    /** Check or apply the database migrations without starting the server. */
+    Migrations {
+        #[arg(value_enum)]
+        mode: MigrationMode,
+    },
+}
+
+// Structs
+/**
+ * The command line arguments. The flags override the config file and the env vars.
+*/
+#[derive(Clone, Debug, Default, Parser)]
+#[command(version, about = "The Rs Watchlist API.", long_about = None)]
+pub struct Cli {
+    /** The TOML file to load the configuration from. Also read from the CONFIG_FILE env var. */
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /** Print the loaded configuration, with the secrets redacted, and exit. */
+    #[arg(long)]
+    pub print_config: bool,
+
+    /** The address to listen on. */
+    #[arg(long)]
+    pub host: Option<IpAddr>,
+
+    /** The port to listen on. */
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /** The URI of the database. */
+    #[arg(long, value_name = "URI")]
+    pub database_uri: Option<String>,
+
+    /** The namespace used in the database. */
+    #[arg(long, value_name = "NAMESPACE")]
+    pub database_namespace: Option<String>,
+
+    /** The name of the database. */
+    #[arg(long, value_name = "NAME")]
+    pub database_name: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/**
+ * The configuration of the API. The values are loaded in layers, each one overriding the previous:
+ * the default values, the config file, the env vars and the command line flags.
+*/
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub log: LogConfig,
+    pub telemetry: TelemetryConfig,
+    pub database: DatabaseConfig,
+    pub security: SecurityConfig,
+    pub rate_limit: LimiterConfig,
+    pub trash: TrashConfig,
+    pub limits: LimitsConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: IpAddr,
+    pub port: u16,
+    #[serde(rename = "request_timeout_secs", with = "duration_secs")]
+    pub request_timeout: Duration,
+    #[serde(rename = "shutdown_timeout_secs", with = "duration_secs")]
+    pub shutdown_timeout: Duration,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub username_max_length: u8,
+    pub title_max_length: u8,
+    pub description_max_length: u8,
+}
+
+// Implementations
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 3000,
+            request_timeout: Duration::from_secs(10),
+            shutdown_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            username_max_length: USERNAME.max_length,
+            title_max_length: TITLE.max_length,
+            description_max_length: DESCRIPTION.max_length,
+        }
+    }
+}
+
+impl ServerConfig {
+    /**
+     * A method to check if the configuration values are coherent.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if self.request_timeout.is_zero() {
+            return Err(String::from(
+                "The server.request_timeout_secs must be greater than 0.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl LimitsConfig {
+    /**
+     * A method to check if the limits are inside the ones allowed by the schema.
+     * The limits can be lowered, but not raised above the rules of the fields.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        let limits = [
+            (self.username_max_length, &USERNAME),
+            (self.title_max_length, &TITLE),
+            (self.description_max_length, &DESCRIPTION),
+        ];
+        for (value, rule) in limits {
+            if !(rule.min_length..=rule.max_length).contains(&value) {
+                return Err(format!(
+                    "The limits.{}_max_length must be between {} and {}.",
+                    rule.name, rule.min_length, rule.max_length
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Config {
+    /**
+     * A method to load the configuration from all the layers and validate it.
+     */
+    pub fn load(cli: &Cli) -> Result<Self, String> {
+        let path = cli
+            .config
+            .clone()
+            .or_else(|| var("CONFIG_FILE").ok().map(PathBuf::from));
+        let config = match path {
+            None => Self::default(),
+            Some(path) => Self::from_file(&path)?,
+        };
+
+        let config = config.merge_env()?.merge_cli(cli);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /**
+     * A method to load the configuration from a TOML file. Missing values use the default values.
+     */
+    pub fn from_file(path: &PathBuf) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Couldn\'t read the config file {}. {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("The config file {} is invalid. {}", path.display(), e))
+    }
+
+    /**
+     * A method to override the configuration with the env vars. Missing vars keep the current values.
+     */
+    pub fn merge_env(mut self) -> Result<Self, String> {
+        // Server
+        self.server.host = env_or("SERVER_HOST", self.server.host)?;
+        self.server.port = env_or("SERVER_PORT", self.server.port)?;
+        self.server.request_timeout = Duration::from_secs(env_or(
+            "SERVER_REQUEST_TIMEOUT_SECS",
+            self.server.request_timeout.as_secs(),
+        )?);
+        self.server.shutdown_timeout = Duration::from_secs(env_or(
+            "SERVER_SHUTDOWN_TIMEOUT_SECS",
+            self.server.shutdown_timeout.as_secs(),
+        )?);
+
+        // Log
+        self.log.format = env_or("LOG_FORMAT", self.log.format)?;
+        self.log.level = env_or("LOG_LEVEL", self.log.level)?;
+
+        // Telemetry
+        self.telemetry.otlp_endpoint =
+            env_opt("TELEMETRY_OTLP_ENDPOINT", self.telemetry.otlp_endpoint);
+        self.telemetry.service_name =
+            env_or("TELEMETRY_SERVICE_NAME", self.telemetry.service_name)?;
+        self.telemetry.sample_ratio =
+            env_or("TELEMETRY_SAMPLE_RATIO", self.telemetry.sample_ratio)?;
+
+        // Database
+        self.database.uri = env_opt("DATABASE_URI", self.database.uri);
+        self.database.user = env_opt("DATABASE_USER", self.database.user);
+        self.database.pass = env_opt("DATABASE_PASS", self.database.pass);
+        self.database.namespace = env_or("DATABASE_NAMESPACE", self.database.namespace)?;
+        self.database.name = env_or("DATABASE_NAME", self.database.name)?;
+        let database = &mut self.database;
+        database.connect_timeout = Duration::from_secs(env_or(
+            "DATABASE_CONNECT_TIMEOUT_SECS",
+            database.connect_timeout.as_secs(),
+        )?);
+        database.health_check_interval = Duration::from_secs(env_or(
+            "DATABASE_HEALTH_CHECK_INTERVAL_SECS",
+            database.health_check_interval.as_secs(),
+        )?);
+        database.reconnect_max_backoff = Duration::from_secs(env_or(
+            "DATABASE_RECONNECT_MAX_BACKOFF_SECS",
+            database.reconnect_max_backoff.as_secs(),
+        )?);
+
+        // Security
+        self.security.token_secret = env_opt("TOKEN_SECRET", self.security.token_secret);
+
+        // Rate limit
+        let limiter = &mut self.rate_limit;
+        limiter.enabled = env_or("RATE_LIMIT_ENABLED", limiter.enabled)?;
+        limiter.ip_burst = env_or("RATE_LIMIT_IP_BURST", limiter.ip_burst)?;
+        limiter.ip_per_minute = env_or("RATE_LIMIT_IP_PER_MINUTE", limiter.ip_per_minute)?;
+        limiter.account_burst = env_or("RATE_LIMIT_ACCOUNT_BURST", limiter.account_burst)?;
+        limiter.account_per_minute =
+            env_or("RATE_LIMIT_ACCOUNT_PER_MINUTE", limiter.account_per_minute)?;
+        limiter.login_max_failures = env_or("LOGIN_MAX_FAILURES", limiter.login_max_failures)?;
+        limiter.login_lockout_base = Duration::from_secs(env_or(
+            "LOGIN_LOCKOUT_BASE_SECS",
+            limiter.login_lockout_base.as_secs(),
+        )?);
+        limiter.login_lockout_max = Duration::from_secs(env_or(
+            "LOGIN_LOCKOUT_MAX_SECS",
+            limiter.login_lockout_max.as_secs(),
+        )?);
+
+        // Trash
+        self.trash.retention_days = env_or("TRASH_RETENTION_DAYS", self.trash.retention_days)?;
+        self.trash.purge_interval = Duration::from_secs(env_or(
+            "TRASH_PURGE_INTERVAL_SECS",
+            self.trash.purge_interval.as_secs(),
+        )?);
+
+        // Limits
+        let limits = &mut self.limits;
+        limits.username_max_length = env_or("USERNAME_MAX_LENGTH", limits.username_max_length)?;
+        limits.title_max_length = env_or("TITLE_MAX_LENGTH", limits.title_max_length)?;
+        limits.description_max_length =
+            env_or("DESCRIPTION_MAX_LENGTH", limits.description_max_length)?;
+
+        Ok(self)
+    }
+
+    /**
+     * A method to override the configuration with the command line flags.
+     */
+    pub fn merge_cli(mut self, cli: &Cli) -> Self {
+        if let Some(host) = cli.host {
+            self.server.host = host;
+        }
+        if let Some(port) = cli.port {
+            self.server.port = port;
+        }
+        if let Some(uri) = &cli.database_uri {
+            self.database.uri = Some(uri.clone());
+        }
+        if let Some(namespace) = &cli.database_namespace {
+            self.database.namespace = namespace.clone();
+        }
+        if let Some(name) = &cli.database_name {
+            self.database.name = name.clone();
+        }
+
+        self
+    }
+
+    /**
+     * A method to check if the configuration is valid. Returns the first invalid value.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        self.server.validate()?;
+        self.log.validate()?;
+        self.telemetry.validate()?;
+        self.database.validate()?;
+        self.security.validate()?;
+        self.rate_limit.validate()?;
+        self.trash.validate()?;
+        self.limits.validate()
+    }
+
+    /**
+     * A method to convert the configuration to TOML. The secrets are redacted.
+     */
+    pub fn to_redacted_toml(&self) -> String {
+        let mut config = self.clone();
+        for secret in [&mut config.database.pass, &mut config.security.token_secret] {
+            if secret.is_some() {
+                *secret = Some(String::from(REDACTED));
+            }
+        }
+
+        toml::to_string(&config).expect("Couldn\'t serialize the config.")
+    }
+}
+
+// Functions
+/**
+ * A method to define the configuration used by the API.
+*/
+pub fn initialize_config(config: Config) {
+    if CONFIG.set(config).is_err() {
+        warn!("The config was already initialized.");
+    }
+}
+
+/**
+ * A method to get the configuration. If it wasn't initialized, the default configuration is used.
+*/
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/**
+ * A method to read some env var, using the default value if it isn't defined.
+*/
+pub fn env_or<T: FromStr>(name: &str, default: T) -> Result<T, String> {
+    match var(name) {
+        Err(_) => Ok(default),
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("The {} env var is invalid.", name)),
+    }
+}
+
+/**
+ * A method to read some optional env var, keeping the current value if it isn't defined.
+*/
+fn env_opt(name: &str, current: Option<String>) -> Option<String> {
+    var(name).ok().or(current)
+}
+
+/**
+ * A module to (de)serialize a Duration as a number of seconds.
+*/
+pub mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_secs)
+    }
+}
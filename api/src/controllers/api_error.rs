@@ -0,0 +1,291 @@
+// Libs
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response as AxumResponse},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::controllers_utils::Response;
+use super::response_body::ResponseBody;
+
+// Enums
+/**
+ * The errors of the API. Each one has a status and a stable code, so the clients can branch on the code.
+ * The messages are only for humans and may change.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    // 400
+    InvalidBody(String),
+    InvalidFields(Vec<FieldError>),
+    InvalidParameters(&'static str),
+    UsernameTaken,
+    DuplicatedMember,
+    MemberNotInWatchlist,
+    AlreadyMember,
+    InvitationPending,
+    InvitationAnswered,
+    OwnerCantLeave,
+    NewOwnerNotMember,
+    WatchlistTrashed,
+    MediaNotEpisodic,
+    MediaHasEpisodes,
+    EpisodeExists,
+
+    // 401
+    InvalidCredentials,
+    InvalidToken,
+
+    // 403
+    ForbiddenWatchlist(&'static str),
+    ForbiddenMedia(&'static str),
+    ForbiddenInvitation,
+    ForbiddenAdminChange,
+    NotAMember,
+
+    // 404
+    UserNotFound,
+    WatchlistNotFound,
+    MediaNotFound,
+    InvitationNotFound,
+    TrashNotFound,
+    EpisodeNotFound,
+    SeasonNotFound,
+
+    // 410
+    InvitationExpired,
+
+    // 429
+    RateLimited,
+    LoginLocked,
+
+    // 500
+    Internal(&'static str),
+
+    // 503
+    DatabaseUnavailable,
+}
+
+// Structs
+/**
+ * Some field of the request that isn't valid.
+ * field: The path of the field, like title or members[0].user.
+*/
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+// Implementations
+impl ApiError {
+    /**
+     * A method to create the error of a single invalid field.
+     */
+    pub fn invalid_field(field: &str, message: &str) -> Self {
+        Self::InvalidFields(vec![FieldError::new(field, message)])
+    }
+
+    /**
+     * A method to get the status of the response.
+     */
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidBody(_)
+            | Self::InvalidFields(_)
+            | Self::InvalidParameters(_)
+            | Self::UsernameTaken
+            | Self::DuplicatedMember
+            | Self::MemberNotInWatchlist
+            | Self::AlreadyMember
+            | Self::InvitationPending
+            | Self::InvitationAnswered
+            | Self::OwnerCantLeave
+            | Self::NewOwnerNotMember
+            | Self::WatchlistTrashed
+            | Self::MediaNotEpisodic
+            | Self::MediaHasEpisodes
+            | Self::EpisodeExists => StatusCode::BAD_REQUEST,
+            Self::InvalidCredentials | Self::InvalidToken => StatusCode::UNAUTHORIZED,
+            Self::ForbiddenWatchlist(_)
+            | Self::ForbiddenMedia(_)
+            | Self::ForbiddenInvitation
+            | Self::ForbiddenAdminChange
+            | Self::NotAMember => StatusCode::FORBIDDEN,
+            Self::UserNotFound
+            | Self::WatchlistNotFound
+            | Self::MediaNotFound
+            | Self::InvitationNotFound
+            | Self::TrashNotFound
+            | Self::EpisodeNotFound
+            | Self::SeasonNotFound => StatusCode::NOT_FOUND,
+            Self::InvitationExpired => StatusCode::GONE,
+            Self::RateLimited | Self::LoginLocked => StatusCode::TOO_MANY_REQUESTS,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::DatabaseUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /**
+     * A method to get the machine-readable code of the error. The codes never change.
+     */
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidBody(_) => "INVALID_BODY",
+            Self::InvalidFields(_) => "INVALID_FIELDS",
+            Self::InvalidParameters(_) => "INVALID_PARAMETERS",
+            Self::UsernameTaken => "USERNAME_TAKEN",
+            Self::DuplicatedMember => "DUPLICATED_MEMBER",
+            Self::MemberNotInWatchlist => "MEMBER_NOT_IN_WATCHLIST",
+            Self::AlreadyMember => "ALREADY_MEMBER",
+            Self::InvitationPending => "INVITATION_PENDING",
+            Self::InvitationAnswered => "INVITATION_ANSWERED",
+            Self::OwnerCantLeave => "OWNER_CANT_LEAVE",
+            Self::NewOwnerNotMember => "NEW_OWNER_NOT_MEMBER",
+            Self::WatchlistTrashed => "WATCHLIST_TRASHED",
+            Self::MediaNotEpisodic => "MEDIA_NOT_EPISODIC",
+            Self::MediaHasEpisodes => "MEDIA_HAS_EPISODES",
+            Self::EpisodeExists => "EPISODE_EXISTS",
+            Self::InvalidCredentials => "INVALID_CREDENTIALS",
+            Self::InvalidToken => "INVALID_TOKEN",
+            Self::ForbiddenWatchlist(_) => "FORBIDDEN_WATCHLIST",
+            Self::ForbiddenMedia(_) => "FORBIDDEN_MEDIA",
+            Self::ForbiddenInvitation => "FORBIDDEN_INVITATION",
+            Self::ForbiddenAdminChange => "FORBIDDEN_ADMIN_CHANGE",
+            Self::NotAMember => "NOT_A_MEMBER",
+            Self::UserNotFound => "USER_NOT_FOUND",
+            Self::WatchlistNotFound => "WATCHLIST_NOT_FOUND",
+            Self::MediaNotFound => "MEDIA_NOT_FOUND",
+            Self::InvitationNotFound => "INVITATION_NOT_FOUND",
+            Self::TrashNotFound => "TRASH_NOT_FOUND",
+            Self::EpisodeNotFound => "EPISODE_NOT_FOUND",
+            Self::SeasonNotFound => "SEASON_NOT_FOUND",
+            Self::InvitationExpired => "INVITATION_EXPIRED",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::LoginLocked => "LOGIN_LOCKED",
+            Self::Internal(_) => "INTERNAL_ERROR",
+            Self::DatabaseUnavailable => "DATABASE_UNAVAILABLE",
+        }
+    }
+
+    /**
+     * A method to get the message of the error.
+     * The actions complete the messages, like "update" in "You don't have permission to update this watchlist.".
+     */
+    pub fn message(&self) -> String {
+        match self {
+            Self::InvalidBody(reason) => format!("The body is invalid. {}", reason),
+            Self::InvalidFields(_) => {
+                String::from("Some fields are invalid. Check the details and try again.")
+            }
+            Self::InvalidParameters(action) => format!(
+                "Couldn\'t {}. Check the parameters and try again.",
+                action
+            ),
+            Self::UsernameTaken => String::from("The username already exists. Try other."),
+            Self::DuplicatedMember => {
+                String::from("Some member is duplicated. Check the parameters and try again.")
+            }
+            Self::MemberNotInWatchlist => {
+                String::from("Some member isn\'t in the watchlist. Invite the user and try again.")
+            }
+            Self::AlreadyMember => String::from("The user is already a member of this watchlist."),
+            Self::InvitationPending => {
+                String::from("The user already has a pending invitation to this watchlist.")
+            }
+            Self::InvitationAnswered => String::from("The invitation was already answered."),
+            Self::OwnerCantLeave => {
+                String::from("The owner can\'t leave the watchlist. Transfer it first.")
+            }
+            Self::NewOwnerNotMember => String::from(
+                "The new owner must be a member of the watchlist. Check the parameters and try again.",
+            ),
+            Self::WatchlistTrashed => {
+                String::from("The watchlist of the media is in the trash. Restore it first.")
+            }
+            Self::MediaNotEpisodic => {
+                String::from("Only the series and animes have episodes. Change the kind first.")
+            }
+            Self::MediaHasEpisodes => {
+                String::from("Only the series and animes have episodes. Delete them first.")
+            }
+            Self::EpisodeExists => {
+                String::from("The media already has this episode. Check the season and number.")
+            }
+            Self::InvalidCredentials => String::from("Check the credentials and try again."),
+            Self::InvalidToken => String::from("Invalid or expired token. Login and try again."),
+            Self::ForbiddenWatchlist(action) => format!(
+                "You don\'t have permission to {} this watchlist.",
+                action
+            ),
+            Self::ForbiddenMedia(action) => {
+                format!("You don\'t have permission to {} this media.", action)
+            }
+            Self::ForbiddenInvitation => {
+                String::from("You don\'t have permission to answer this invitation.")
+            }
+            Self::ForbiddenAdminChange => {
+                String::from("Only the owner can add, remove or change an admin.")
+            }
+            Self::NotAMember => String::from("You aren\'t a member of this watchlist."),
+            Self::UserNotFound => String::from("User not found. Check the id and try again."),
+            Self::WatchlistNotFound => {
+                String::from("Watchlist not found. Check the id and try again.")
+            }
+            Self::MediaNotFound => String::from("Media not found. Check the id and try again."),
+            Self::InvitationNotFound => {
+                String::from("Invitation not found. Check the id and try again.")
+            }
+            Self::TrashNotFound => {
+                String::from("Nothing found in the trash. Check the id and try again.")
+            }
+            Self::EpisodeNotFound => {
+                String::from("Episode not found. Check the id and try again.")
+            }
+            Self::SeasonNotFound => {
+                String::from("The media has no episode in this season. Check the season and try again.")
+            }
+            Self::InvitationExpired => String::from("The invitation expired. Ask for a new one."),
+            Self::RateLimited => String::from("Too many requests. Try again later."),
+            Self::LoginLocked => String::from("Too many failed logins. Try again later."),
+            Self::Internal(action) => {
+                format!("Couldn\'t {}. Please contact the admin.", action)
+            }
+            Self::DatabaseUnavailable => {
+                String::from("The database is unavailable. Try again later.")
+            }
+        }
+    }
+
+    /**
+     * A method to get the invalid fields of the error, if any.
+     */
+    pub fn details(&self) -> Option<Vec<FieldError>> {
+        match self {
+            Self::InvalidFields(fields) => Some(fields.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<ApiError> for Response {
+    fn from(error: ApiError) -> Self {
+        (error.status(), ResponseBody::error(&error))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> AxumResponse {
+        Response::from(self).into_response()
+    }
+}
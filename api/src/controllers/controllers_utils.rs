@@ -1,14 +1,24 @@
 // Libs
+use std::collections::HashSet;
+
 use axum::{http::StatusCode, Json};
 use surrealdb::sql::{Id, Thing};
 use tracing::{error, info, warn};
 
+use super::api_error::ApiError;
 use super::response_body::ResponseBody;
+use crate::config::config;
+use crate::limiter::limiter;
+use crate::logger::record_user_id;
+use crate::metrics::count_login_failure;
+use crate::models::episode_model::Episode;
+use crate::models::invitation_model::Invitation;
 use crate::models::media_model::Media;
 use crate::models::model_trait::ModelTrait;
 use crate::models::user_model::User;
-use crate::models::watchlist_model::Watchlist;
-use crate::security::is_valid_field;
+use crate::models::watchlist_model::{Member, MemberRequest, Role, Watchlist};
+use crate::security::verify_dummy_password;
+use crate::validation::{normalize, username_key, USERNAME};
 
 // Types
 pub type Response = (StatusCode, Json<ResponseBody>);
@@ -18,59 +28,76 @@ pub type Response = (StatusCode, Json<ResponseBody>);
 /**
  * A method to get some media in the database using its id.
 */
-pub async fn get_media_from_id(media_id: Id) -> Result<Media, Response> {
+pub async fn get_media_from_id(media_id: Id) -> Result<Media, ApiError> {
     // Try to get the media using his id.
     match Media::from_id(media_id).await {
         Err(e) => {
             error!("Couldn\'t get the media. {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ResponseBody::error("Couldn\'t get the media. Please call the admin."),
-            ))
+            Err(ApiError::Internal("get the media"))
         }
-        Ok(None) => Err((
-            StatusCode::NOT_FOUND,
-            ResponseBody::error("media not found. Check the id and try again."),
-        )),
-        Ok(Some(media)) => Ok(media),
+        Ok(Some(media)) if !media.is_trashed() => Ok(media),
+        Ok(_) => Err(ApiError::MediaNotFound),
+    }
+}
+
+/**
+ * A method to get some episode of the media in the database using its id.
+*/
+pub async fn get_episode_from_id(media_id: &Thing, episode_id: Id) -> Result<Episode, ApiError> {
+    match Episode::from_id(episode_id).await {
+        Err(e) => {
+            error!("Couldn\'t get the episode. {}", e);
+            Err(ApiError::Internal("get the episode"))
+        }
+        Ok(Some(episode)) if &episode.media == media_id => Ok(episode),
+        Ok(_) => Err(ApiError::EpisodeNotFound),
     }
 }
 
 // User
 /**
- * A method to login an user. Uses its id and password.
+ * A method to login an user. Uses its username and password.
+ * The unknown users get the same error as the wrong passwords.
 */
-pub async fn login_user(
-    user_auth: (String, Option<String>),
-    is_username: bool,
-) -> Result<User, Response> {
+pub async fn login_user(user_auth: (String, Option<String>)) -> Result<User, ApiError> {
     info!("Trying to login the user...");
 
-    // Define the default error_message.
-    let response_error = (
-        StatusCode::UNAUTHORIZED,
-        ResponseBody::error("Check the credentials and try again."),
-    );
-
     // Check the authorization.
-    let (user_identifier, password) = match user_auth {
-        (user_identifier, Some(pass)) => (user_identifier, pass),
+    let (username, password) = match user_auth {
+        (username, Some(pass)) => (username, pass),
         (_, None) => {
             info!("Password not included.");
-            return Err(response_error);
+            count_login_failure("missing_password");
+            return Err(ApiError::InvalidCredentials);
         }
     };
 
     // Get the user from the database and check the password.
-    let user_db = if is_username {
-        get_user_from_username(&user_identifier).await?
-    } else {
-        get_user_from_id(Id::from(user_identifier)).await?
+    let key = username_key(&username);
+    // An invalid username can't exist, so it fails like an unknown one.
+    // Both take the time of a password check, so the time doesn't tell if the user exists.
+    let mut user_db = match get_user_from_username(&username).await {
+        Err(ApiError::UserNotFound | ApiError::InvalidFields(_)) => {
+            verify_dummy_password(password.as_bytes());
+            limiter().register_login_failure(&key);
+            count_login_failure("unknown_user");
+            return Err(ApiError::InvalidCredentials);
+        }
+        Err(e) => return Err(e),
+        Ok(user_db) => user_db,
     };
-    match user_db.is_login_valid(password) {
-        false => Err(response_error),
+    match user_db.is_login_valid(password).await {
+        false => {
+            limiter().register_login_failure(&key);
+            count_login_failure("wrong_password");
+            Err(ApiError::InvalidCredentials)
+        }
         true => {
+            if let Some(user_id) = &user_db.id {
+                record_user_id(user_id);
+            }
             info!("User successfully logged in.");
+            limiter().register_login_success(&key);
             Ok(user_db)
         }
     }
@@ -79,113 +106,121 @@ pub async fn login_user(
 /**
  * A method to get some user in the database using his id.
 */
-pub async fn get_user_from_id(user_id: Id) -> Result<User, Response> {
+pub async fn get_user_from_id(user_id: Id) -> Result<User, ApiError> {
     // Try to get the user using his id.
     match User::from_id(user_id).await {
         Err(e) => {
             error!("Couldn\'t get the user. {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ResponseBody::error("Couldn\'t get the user. Please call the admin."),
-            ))
+            Err(ApiError::Internal("get the user"))
         }
-        Ok(None) => Err((
-            StatusCode::UNAUTHORIZED,
-            ResponseBody::error("User not found. Check the id and try again."),
-        )),
+        Ok(None) => Err(ApiError::UserNotFound),
         Ok(Some(user)) => Ok(user),
     }
 }
 
+/**
+ * A method to check some new username against the configured limit.
+ * The existing usernames only follow the rule, so lowering the limit doesn't lock their users out.
+*/
+pub fn check_username_limit(username: &str) -> Result<(), ApiError> {
+    let mut errors = Vec::new();
+    USERNAME.check(&mut errors, username, config().limits.username_max_length);
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ApiError::InvalidFields(errors)),
+    }
+}
+
 /**
  * A method to get some user in the database using his username.
 */
-pub async fn get_user_from_username(username: &str) -> Result<User, Response> {
-    // Check if the username is valid.
-    if !is_valid_field(username, 20) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            ResponseBody::error("The username is invalid. Check the parameters and try again."),
+pub async fn get_user_from_username(username: &str) -> Result<User, ApiError> {
+    // Check if the username is valid. The configured limit isn't used, it only applies to the new usernames.
+    let username = normalize(username);
+    if !USERNAME.is_valid(&username, USERNAME.max_length) {
+        return Err(ApiError::invalid_field(
+            "username",
+            "The username is invalid.",
         ));
     }
 
     // try to get the user by his username.
-    match User::from_username(username).await {
-        Err(_) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            ResponseBody::error("Couldn\'t get the user. Please call the admin."),
-        )),
-        Ok(None) => Err((
-            StatusCode::UNAUTHORIZED,
-            ResponseBody::error("User not found. Check the username and try again."),
-        )),
+    match User::from_username(&username).await {
+        Err(e) => {
+            error!("Couldn\'t get the user. {}", e);
+            Err(ApiError::Internal("get the user"))
+        }
+        Ok(None) => Err(ApiError::UserNotFound),
         Ok(Some(user)) => Ok(user),
     }
 }
 
+// Invitation
+/**
+ * A method to get some invitation in the database using its id.
+*/
+pub async fn get_invitation_from_id(invitation_id: Id) -> Result<Invitation, ApiError> {
+    // Try to get the invitation using its id.
+    match Invitation::from_id(invitation_id).await {
+        Err(e) => {
+            error!("Couldn\'t get the invitation. {}", e);
+            Err(ApiError::Internal("get the invitation"))
+        }
+        Ok(None) => Err(ApiError::InvitationNotFound),
+        Ok(Some(invitation)) => Ok(invitation),
+    }
+}
+
 // Watchlist
 /**
  * A method to get some watchlist in the database using its id.
 */
-pub async fn get_watchlist_from_id(watchlist_id: Id) -> Result<Watchlist, Response> {
+pub async fn get_watchlist_from_id(watchlist_id: Id) -> Result<Watchlist, ApiError> {
     // Try to get the watchlist using his id.
     match Watchlist::from_id(watchlist_id).await {
         Err(e) => {
             error!("Couldn\'t get the watchlist. {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ResponseBody::error("Couldn\'t get the watchlist. Please contact the admin."),
-            ))
+            Err(ApiError::Internal("get the watchlist"))
         }
-        Ok(None) => {
-            info!("watchlist not found.");
-            Err((
-                StatusCode::NOT_FOUND,
-                ResponseBody::error("watchlist not found. Check the id and try again."),
-            ))
-        }
-        Ok(Some(watchlist)) => {
+        Ok(Some(watchlist)) if !watchlist.is_trashed() => {
             info!("watchlist found.");
             Ok(watchlist)
         }
+        Ok(_) => {
+            info!("watchlist not found.");
+            Err(ApiError::WatchlistNotFound)
+        }
     }
 }
 
 /**
  * A method to check if all members are valid.
+ * Users only become members accepting an invitation, so only the current members are accepted.
 */
-pub async fn are_members_valid(owner: &Thing, members: &Vec<String>) -> Result<(), Response> {
+pub fn are_members_valid(
+    current_members: &[Member],
+    members: &[MemberRequest],
+) -> Result<(), ApiError> {
     info!("Checking if all members from the watchlist are valid.");
-    if members.contains(&owner.id.to_string()) {
-        warn!("The owner is a member.");
-        return Err((
-            StatusCode::BAD_REQUEST,
-            ResponseBody::error("Some member is valid. Check the parameters and try again."),
-        ));
+
+    // Check if some member is duplicated.
+    let mut unique_members = HashSet::new();
+    if !members
+        .iter()
+        .all(|member| unique_members.insert(&member.user))
+    {
+        warn!("Some member is duplicated.");
+        return Err(ApiError::DuplicatedMember);
     }
 
-    // Check if all members are valid.
-    for member_id in members {
-        match User::from_id(Id::from(member_id)).await {
-            Err(e) => {
-                error!("Couldn\'t check if all members are valid. {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    ResponseBody::error(
-                        "Couldn\'t create the watchlist. Please contact the admin.",
-                    ),
-                ));
-            }
-            Ok(None) => {
-                info!("Some member is invalid.");
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    ResponseBody::error(
-                        "Some member is valid. Check the parameters and try again.",
-                    ),
-                ));
-            }
-            Ok(Some(_)) => (),
+    // Check if all members are already in the watchlist.
+    for member in members {
+        let is_current = current_members
+            .iter()
+            .any(|current| current.user.id.to_string() == member.user);
+        if !is_current {
+            info!("Some member isn\'t in the watchlist.");
+            return Err(ApiError::MemberNotInWatchlist);
         }
     }
 
@@ -193,35 +228,59 @@ pub async fn are_members_valid(owner: &Thing, members: &Vec<String>) -> Result<(
     Ok(())
 }
 
+/**
+ * A method to check if the user can replace the watchlist members with the given ones.
+ * Only the owner can add, remove or change the role of an admin.
+*/
+pub fn can_change_members(
+    watchlist: &Watchlist,
+    user_id: &Thing,
+    members: &[Member],
+) -> Result<(), ApiError> {
+    if watchlist.is_owner(user_id) {
+        return Ok(());
+    }
+
+    // Check if the admins are the same.
+    let old_admins: Vec<&Member> = watchlist
+        .members
+        .iter()
+        .filter(|member| member.role == Role::Admin)
+        .collect();
+    let new_admins: Vec<&Member> = members
+        .iter()
+        .filter(|member| member.role == Role::Admin)
+        .collect();
+    let same_admins = old_admins.len() == new_admins.len()
+        && old_admins.iter().all(|admin| new_admins.contains(admin));
+    if !same_admins {
+        warn!("Only the owner can manage the admins.");
+        return Err(ApiError::ForbiddenAdminChange);
+    }
+
+    Ok(())
+}
+
 /**
  * A method to get all the related watchlist to the user.
 */
-pub async fn get_all_watchlist_from_user(logged_user: &User) -> Result<Vec<Watchlist>, Response> {
-    info!(
-        "Getting all the watchlists from {}.",
-        logged_user.id.as_ref().unwrap()
-    );
+pub async fn get_all_watchlist_from_user(user_id: &Thing) -> Result<Vec<Watchlist>, ApiError> {
+    info!("Getting all the watchlists from {}.", user_id);
 
     // Get owned watchlists.
-    let mut watchlists = match logged_user.get_watchlists_as_owner().await {
+    let mut watchlists = match User::get_watchlists_as_owner(user_id).await {
         Err(e) => {
             error!("Couldn\'t get the owned watchlists. {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ResponseBody::error("Couldn\'t get the watchlists. Please contact the admin."),
-            ));
+            return Err(ApiError::Internal("get the watchlists"));
         }
         Ok(watchlists) => watchlists,
     };
 
     // Get the watchlists as member.
-    let mut member_watchlists = match logged_user.get_watchlists_as_member().await {
+    let mut member_watchlists = match User::get_watchlists_as_member(user_id).await {
         Err(e) => {
             error!("Couldn\'t get the watchlists as member. {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ResponseBody::error("Couldn\'t get the watchlists. Please contact the admin."),
-            ));
+            return Err(ApiError::Internal("get the watchlists"));
         }
         Ok(member_watchlists) => member_watchlists,
     };
@@ -0,0 +1,323 @@
+// Libs
+use axum::{extract::Path, http::StatusCode};
+use surrealdb::sql::{Id, Thing};
+use tracing::{error, info, warn};
+
+use super::api_error::ApiError;
+use super::controllers_utils::*;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::extractors::{auth_extractor::AuthUser, validated_json_extractor::ValidatedJson};
+use crate::models::{
+    episode_model::{Episode, EpisodeRequest, EpisodeResponse, WatchedRequest},
+    media_model::{Media, MediaResponse},
+    model_trait::ModelTrait,
+    watchlist_model::Role,
+};
+use crate::validation::SEASON;
+
+// Functions
+/**
+ * GET /media/{media_id}/episode
+ * Authorization: Bearer
+ * A method to get the episodes of a media, sorted by season and number.
+*/
+#[utoipa::path(
+    get,
+    path = "/media/{media_id}/episode",
+    tag = "episode",
+    summary = "Get the episodes of a media.",
+    description = "A method to get the episodes of a media, sorted by season and number.",
+    params(("media_id" = String, Path, description = "The id of the media.")),
+    responses(
+        (status = 200, description = "The episodes of the media.", body = ResponseData<Vec<EpisodeResponse>>),
+        (status = 403, description = "The user isn\'t a member of the watchlist.", body = ResponseBody),
+        (status = 404, description = "The media wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn get_episodes(AuthUser(user_id): AuthUser, Path(media_id): Path<String>) -> Response {
+    let media = match get_media_with_role(&user_id, media_id, Role::Viewer, "get").await {
+        Err(e) => return e.into(),
+        Ok(media) => media,
+    };
+
+    match Episode::from_media(media.id.as_ref().unwrap()).await {
+        Err(e) => {
+            error!("Couldn\'t get the episodes. {}", e);
+            ApiError::Internal("get the episodes").into()
+        }
+        Ok(episodes) => (
+            StatusCode::OK,
+            ResponseBody::success(
+                episodes
+                    .iter()
+                    .map(Episode::to_episode_response)
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+    }
+}
+
+/**
+ * POST /media/{media_id}/episode
+ * Authorization: Bearer
+ * BODY: EpisodeRequest
+ * A method to add an episode to a series or an anime.
+*/
+#[utoipa::path(
+    post,
+    path = "/media/{media_id}/episode",
+    tag = "episode",
+    summary = "Add an episode to a series or an anime.",
+    description = "A method to add an episode to a series or an anime.",
+    params(("media_id" = String, Path, description = "The id of the media.")),
+    request_body = EpisodeRequest,
+    responses(
+        (status = 201, description = "The episode was added.", body = ResponseData<EpisodeResponse>),
+        (status = 400, description = "The request isn\'t valid, the media has no episodes or already has the episode.", body = ResponseBody),
+        (status = 403, description = "The user can\'t update the media.", body = ResponseBody),
+        (status = 404, description = "The media wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn post_episode(
+    AuthUser(user_id): AuthUser,
+    Path(media_id): Path<String>,
+    ValidatedJson(new_episode): ValidatedJson<EpisodeRequest>,
+) -> Response {
+    let mut media = match get_media_with_role(&user_id, media_id, Role::Editor, "update").await {
+        Err(e) => return e.into(),
+        Ok(media) => media,
+    };
+    if !media.kind.is_episodic() {
+        warn!("The media has no episodes.");
+        return ApiError::MediaNotEpisodic.into();
+    }
+
+    // Check if the media already has the episode.
+    let media_id = media.id.clone().unwrap();
+    let episodes = match Episode::from_media(&media_id).await {
+        Err(e) => {
+            error!("Couldn\'t get the episodes. {}", e);
+            return ApiError::Internal("add the episode").into();
+        }
+        Ok(episodes) => episodes,
+    };
+    if episodes
+        .iter()
+        .any(|e| e.season == new_episode.season && e.number == new_episode.number)
+    {
+        info!("The media already has the episode.");
+        return ApiError::EpisodeExists.into();
+    }
+
+    // Create the episode and refresh the progress of the media.
+    let mut new_episode = Episode::new(media_id, new_episode);
+    if let Err(e) = new_episode.sync().await {
+        warn!("Couldn\'t create the episode. {}", e);
+        return ApiError::InvalidParameters("add the episode").into();
+    }
+    if let Err(e) = media.sync_progress().await {
+        error!("Couldn\'t refresh the progress of the media. {}", e);
+        return ApiError::Internal("add the episode").into();
+    }
+
+    (
+        StatusCode::CREATED,
+        ResponseBody::success(new_episode.to_episode_response()),
+    )
+}
+
+/**
+ * DELETE /media/{media_id}/episode/{episode_id}
+ * Authorization: Bearer
+ * A method to delete an episode of a media.
+*/
+#[utoipa::path(
+    delete,
+    path = "/media/{media_id}/episode/{episode_id}",
+    tag = "episode",
+    summary = "Delete an episode of a media.",
+    description = "A method to delete an episode of a media.",
+    params(
+        ("media_id" = String, Path, description = "The id of the media."),
+        ("episode_id" = String, Path, description = "The id of the episode."),
+    ),
+    responses(
+        (status = 200, description = "The episode was deleted. The media with its new progress.", body = ResponseData<MediaResponse>),
+        (status = 403, description = "The user can\'t update the media.", body = ResponseBody),
+        (status = 404, description = "The media or the episode wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn delete_episode(
+    AuthUser(user_id): AuthUser,
+    Path((media_id, episode_id)): Path<(String, String)>,
+) -> Response {
+    let mut media = match get_media_with_role(&user_id, media_id, Role::Editor, "update").await {
+        Err(e) => return e.into(),
+        Ok(media) => media,
+    };
+    let episode = match get_episode_from_id(media.id.as_ref().unwrap(), Id::from(episode_id)).await
+    {
+        Err(e) => return e.into(),
+        Ok(episode) => episode,
+    };
+
+    // Delete the episode and refresh the progress of the media.
+    if let Err(e) = episode.delete().await {
+        error!("Couldn\'t delete the episode. {}", e);
+        return ApiError::Internal("delete the episode").into();
+    }
+    sync_progress(&mut media, "delete the episode").await
+}
+
+/**
+ * POST /media/{media_id}/episode/{episode_id}/watched
+ * Authorization: Bearer
+ * BODY: WatchedRequest
+ * A method to mark an episode as watched or not.
+*/
+#[utoipa::path(
+    post,
+    path = "/media/{media_id}/episode/{episode_id}/watched",
+    tag = "episode",
+    summary = "Mark an episode as watched or not.",
+    description = "A method to mark an episode as watched or not. An episode already watched keeps when it was watched.",
+    params(
+        ("media_id" = String, Path, description = "The id of the media."),
+        ("episode_id" = String, Path, description = "The id of the episode."),
+    ),
+    request_body = WatchedRequest,
+    responses(
+        (status = 200, description = "The media with its new progress.", body = ResponseData<MediaResponse>),
+        (status = 400, description = "The request isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user can\'t update the media.", body = ResponseBody),
+        (status = 404, description = "The media or the episode wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn post_episode_watched(
+    AuthUser(user_id): AuthUser,
+    Path((media_id, episode_id)): Path<(String, String)>,
+    ValidatedJson(request): ValidatedJson<WatchedRequest>,
+) -> Response {
+    let mut media = match get_media_with_role(&user_id, media_id, Role::Editor, "update").await {
+        Err(e) => return e.into(),
+        Ok(media) => media,
+    };
+    let mut episode =
+        match get_episode_from_id(media.id.as_ref().unwrap(), Id::from(episode_id)).await {
+            Err(e) => return e.into(),
+            Ok(episode) => episode,
+        };
+
+    // Mark the episode and refresh the progress of the media.
+    episode.set_watched(request.watched);
+    if let Err(e) = episode.sync().await {
+        error!("Couldn\'t update the episode. {}", e);
+        return ApiError::Internal("update the episode").into();
+    }
+    sync_progress(&mut media, "update the episode").await
+}
+
+/**
+ * POST /media/{media_id}/season/{season}/watched
+ * Authorization: Bearer
+ * BODY: WatchedRequest
+ * A method to mark all the episodes of a season as watched or not.
+*/
+#[utoipa::path(
+    post,
+    path = "/media/{media_id}/season/{season}/watched",
+    tag = "episode",
+    summary = "Mark all the episodes of a season as watched or not.",
+    description = "A method to mark all the episodes of a season as watched or not. The episodes already watched keep when they were watched.",
+    params(
+        ("media_id" = String, Path, description = "The id of the media."),
+        ("season" = u16, Path, description = "The season. The season 0 has the specials."),
+    ),
+    request_body = WatchedRequest,
+    responses(
+        (status = 200, description = "The media with its new progress.", body = ResponseData<MediaResponse>),
+        (status = 400, description = "The request or the season isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user can\'t update the media.", body = ResponseBody),
+        (status = 404, description = "The media or the season wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn post_season_watched(
+    AuthUser(user_id): AuthUser,
+    Path((media_id, season)): Path<(String, String)>,
+    ValidatedJson(request): ValidatedJson<WatchedRequest>,
+) -> Response {
+    // Check the season, since it's a part of the path.
+    let season: u16 = match season.parse() {
+        Ok(season) if u32::from(season) <= SEASON.max => season,
+        _ => {
+            info!("The season is invalid.");
+            let message = format!(
+                "The season must be between {} and {}.",
+                SEASON.min, SEASON.max
+            );
+            return ApiError::invalid_field("season", &message).into();
+        }
+    };
+
+    let mut media = match get_media_with_role(&user_id, media_id, Role::Editor, "update").await {
+        Err(e) => return e.into(),
+        Ok(media) => media,
+    };
+
+    // Check if the media has the season.
+    let media_id = media.id.clone().unwrap();
+    match Episode::from_media(&media_id).await {
+        Err(e) => {
+            error!("Couldn\'t get the episodes. {}", e);
+            return ApiError::Internal("update the season").into();
+        }
+        Ok(episodes) if !episodes.iter().any(|e| e.season == season) => {
+            info!("The media has no episode in the season.");
+            return ApiError::SeasonNotFound.into();
+        }
+        Ok(_) => (),
+    }
+
+    // Mark the episodes and refresh the progress of the media.
+    if let Err(e) = Episode::set_season_watched(&media_id, season, request.watched).await {
+        error!("Couldn\'t update the season. {}", e);
+        return ApiError::Internal("update the season").into();
+    }
+    sync_progress(&mut media, "update the season").await
+}
+
+/**
+ * A method to get some media if the user has the role in its watchlist.
+ * action: What the user wants to do with the media, like "update".
+*/
+async fn get_media_with_role(
+    user_id: &Thing,
+    media_id: String,
+    role: Role,
+    action: &'static str,
+) -> Result<Media, ApiError> {
+    let media = get_media_from_id(Id::from(media_id)).await?;
+    let watchlist = get_watchlist_from_id(media.watchlist.id.clone()).await?;
+    if !watchlist.has_role(user_id, role) {
+        warn!("User doesn\'t have permission to {} the media.", action);
+        return Err(ApiError::ForbiddenMedia(action));
+    }
+
+    Ok(media)
+}
+
+/**
+ * A method to refresh the progress of the media and answer with it.
+*/
+async fn sync_progress(media: &mut Media, action: &'static str) -> Response {
+    match media.sync_progress().await {
+        Err(e) => {
+            error!("Couldn\'t refresh the progress of the media. {}", e);
+            ApiError::Internal(action).into()
+        }
+        Ok(_) => (
+            StatusCode::OK,
+            ResponseBody::success(media.to_media_response()),
+        ),
+    }
+}
@@ -0,0 +1,70 @@
+// Libs
+use axum::http::StatusCode;
+
+use super::controllers_utils::*;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::models::health_model::{Readiness, Version};
+
+// Functions
+/**
+ * GET /healthz
+ * A method to check if the API is alive. The database isn't checked.
+*/
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "probes",
+    summary = "Check if the API is alive.",
+    description = "A method to check if the API is alive. The database isn\'t checked.",
+    security(()),
+    responses(
+        (status = 200, description = "The API is alive.", body = ResponseBody),
+    )
+)]
+pub async fn get_healthz() -> Response {
+    (StatusCode::OK, ResponseBody::success_no_data())
+}
+
+/**
+ * GET /readyz
+ * A method to check if the API can receive requests. The database must answer and all the migrations must be applied.
+*/
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "probes",
+    summary = "Check if the API can receive requests.",
+    description = "A method to check if the API can receive requests. The database must answer and all the migrations must be applied.",
+    security(()),
+    responses(
+        (status = 200, description = "The API is ready.", body = ResponseData<Readiness>),
+        (status = 503, description = "The API isn\'t ready.", body = ResponseData<Readiness>),
+    )
+)]
+pub async fn get_readyz() -> Response {
+    let readiness = Readiness::check().await;
+    let status = match readiness.is_ready() {
+        false => StatusCode::SERVICE_UNAVAILABLE,
+        true => StatusCode::OK,
+    };
+    (status, ResponseBody::success(readiness))
+}
+
+/**
+ * GET /version
+ * A method to get the version of the running build.
+*/
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "probes",
+    summary = "Get the version of the running build.",
+    description = "A method to get the version of the running build.",
+    security(()),
+    responses(
+        (status = 200, description = "The running build.", body = ResponseData<Version>),
+    )
+)]
+pub async fn get_version() -> Response {
+    (StatusCode::OK, ResponseBody::success(Version::current()))
+}
@@ -0,0 +1,263 @@
+// Libs
+use axum::{extract::Path, http::StatusCode};
+use surrealdb::sql::{Id, Thing};
+use tracing::{error, info, warn};
+
+use super::api_error::ApiError;
+use super::controllers_utils::*;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::extractors::{auth_extractor::AuthUser, validated_json_extractor::ValidatedJson};
+use crate::models::{
+    invitation_model::{Invitation, InvitationRequest, InvitationResponse, InvitationStatus},
+    model_trait::ModelTrait,
+    watchlist_model::{Member, Role, WatchlistResponse},
+};
+
+// Functions
+/**
+ * POST /watchlist/{watchlist_id}/invitation
+ * Authorization: Bearer
+ * BODY: InvitationRequest
+ * A method to invite an user to the watchlist.
+*/
+#[utoipa::path(
+    post,
+    path = "/watchlist/{watchlist_id}/invitation",
+    tag = "invitation",
+    summary = "Invite an user to the watchlist.",
+    description = "A method to invite an user to the watchlist.",
+    params(("watchlist_id" = String, Path, description = "The id of the watchlist.")),
+    request_body = InvitationRequest,
+    responses(
+        (status = 201, description = "The invitation was sent.", body = ResponseData<InvitationResponse>),
+        (status = 400, description = "The user can\'t be invited.", body = ResponseBody),
+        (status = 403, description = "The user can\'t invite with the given role.", body = ResponseBody),
+        (status = 404, description = "The watchlist or the invitee wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn post_watchlist_invitation(
+    AuthUser(user_id): AuthUser,
+    Path(watchlist_id): Path<String>,
+    ValidatedJson(invitation): ValidatedJson<InvitationRequest>,
+) -> Response {
+    // Get the watchlist.
+    let watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
+        Err(e) => return e.into(),
+        Ok(watchlist) => watchlist,
+    };
+
+    // Check if the user can manage the members of the watchlist.
+    if !watchlist.has_role(&user_id, Role::Admin) {
+        warn!("User doesn\'t have permission to invite to the watchlist.");
+        return ApiError::ForbiddenWatchlist("invite to").into();
+    }
+    if invitation.role == Role::Admin && !watchlist.is_owner(&user_id) {
+        warn!("Only the owner can invite an admin.");
+        return ApiError::ForbiddenAdminChange.into();
+    }
+
+    // Get the invited user.
+    let invitee = match get_user_from_username(&invitation.username).await {
+        Err(e) => return e.into(),
+        Ok(invitee) => invitee.id.unwrap(),
+    };
+
+    // Check if the user can be invited.
+    if watchlist.has_role(&invitee, Role::Viewer) {
+        info!("The user is already a member.");
+        return ApiError::AlreadyMember.into();
+    }
+    let watchlist_id = watchlist.id.unwrap();
+    match Invitation::has_pending(&watchlist_id, &invitee).await {
+        Err(e) => {
+            error!("Couldn\'t check the pending invitations. {}", e);
+            return ApiError::Internal("create the invitation").into();
+        }
+        Ok(true) => {
+            info!("The user already has a pending invitation.");
+            return ApiError::InvitationPending.into();
+        }
+        Ok(false) => (),
+    }
+
+    // Try to synchronize the invitation in the database.
+    let mut invitation = Invitation::new(watchlist_id, user_id, invitee, invitation.role);
+    match invitation.sync().await {
+        Err(e) => {
+            warn!("Couldn\'t create the invitation. {}", e);
+            ApiError::InvalidParameters("create the invitation").into()
+        }
+        Ok(_) => (
+            StatusCode::CREATED,
+            ResponseBody::success(invitation.to_invitation_response()),
+        ),
+    }
+}
+
+/**
+ * GET /invitation
+ * Authorization: Bearer
+ * A method to get all the pending invitations sent to the user.
+*/
+#[utoipa::path(
+    get,
+    path = "/invitation",
+    tag = "invitation",
+    summary = "Get all the pending invitations sent to the user.",
+    description = "A method to get all the pending invitations sent to the user.",
+    responses(
+        (status = 200, description = "The pending invitations sent to the user.", body = ResponseData<Vec<InvitationResponse>>),
+    )
+)]
+pub async fn get_invitations(AuthUser(user_id): AuthUser) -> Response {
+    match Invitation::get_pending_from_invitee(&user_id).await {
+        Err(e) => {
+            error!("Couldn\'t get the invitations. {}", e);
+            ApiError::Internal("get the invitations").into()
+        }
+        Ok(invitations) => {
+            let invitations: Vec<InvitationResponse> = invitations
+                .iter()
+                .map(|invitation| invitation.to_invitation_response())
+                .collect();
+            (StatusCode::OK, ResponseBody::success(invitations))
+        }
+    }
+}
+
+/**
+ * POST /invitation/{invitation_id}/accept
+ * Authorization: Bearer
+ * A method to accept an invitation. The user becomes a member of the watchlist.
+*/
+#[utoipa::path(
+    post,
+    path = "/invitation/{invitation_id}/accept",
+    tag = "invitation",
+    summary = "Accept an invitation.",
+    description = "A method to accept an invitation. The user becomes a member of the watchlist.",
+    params(("invitation_id" = String, Path, description = "The id of the invitation.")),
+    responses(
+        (status = 200, description = "The user joined the watchlist.", body = ResponseData<WatchlistResponse>),
+        (status = 400, description = "The id isn\'t valid or the invitation isn\'t pending.", body = ResponseBody),
+        (status = 403, description = "The invitation wasn\'t sent to the user.", body = ResponseBody),
+        (status = 404, description = "The invitation wasn\'t found.", body = ResponseBody),
+        (status = 410, description = "The invitation expired.", body = ResponseBody),
+    )
+)]
+pub async fn post_invitation_accept(
+    AuthUser(user_id): AuthUser,
+    Path(invitation_id): Path<String>,
+) -> Response {
+    // Get the invitation.
+    let mut invitation = match get_pending_invitation(&user_id, invitation_id).await {
+        Err(e) => return e.into(),
+        Ok(invitation) => invitation,
+    };
+
+    // Add the user to the watchlist.
+    let mut watchlist = match get_watchlist_from_id(invitation.watchlist.id.clone()).await {
+        Err(e) => return e.into(),
+        Ok(watchlist) => watchlist,
+    };
+    if !watchlist.has_role(&user_id, Role::Viewer) {
+        watchlist.members.push(Member {
+            user: user_id,
+            role: invitation.role,
+        });
+        if let Err(e) = watchlist.sync().await {
+            error!("Couldn\'t add the member to the watchlist. {}", e);
+            return ApiError::Internal("accept the invitation").into();
+        }
+    }
+
+    // Mark the invitation as accepted.
+    invitation.status = InvitationStatus::Accepted;
+    if let Err(e) = invitation.sync().await {
+        error!("Couldn\'t update the invitation. {}", e);
+        return ApiError::Internal("accept the invitation").into();
+    }
+
+    info!("{} accepted.", invitation.id.as_ref().unwrap());
+    (
+        StatusCode::OK,
+        ResponseBody::success(watchlist.to_watchlist_response()),
+    )
+}
+
+/**
+ * POST /invitation/{invitation_id}/decline
+ * Authorization: Bearer
+ * A method to decline an invitation.
+*/
+#[utoipa::path(
+    post,
+    path = "/invitation/{invitation_id}/decline",
+    tag = "invitation",
+    summary = "Decline an invitation.",
+    description = "A method to decline an invitation.",
+    params(("invitation_id" = String, Path, description = "The id of the invitation.")),
+    responses(
+        (status = 200, description = "The invitation was declined.", body = ResponseData<InvitationResponse>),
+        (status = 400, description = "The id isn\'t valid or the invitation isn\'t pending.", body = ResponseBody),
+        (status = 403, description = "The invitation wasn\'t sent to the user.", body = ResponseBody),
+        (status = 404, description = "The invitation wasn\'t found.", body = ResponseBody),
+        (status = 410, description = "The invitation expired.", body = ResponseBody),
+    )
+)]
+pub async fn post_invitation_decline(
+    AuthUser(user_id): AuthUser,
+    Path(invitation_id): Path<String>,
+) -> Response {
+    // Get the invitation.
+    let mut invitation = match get_pending_invitation(&user_id, invitation_id).await {
+        Err(e) => return e.into(),
+        Ok(invitation) => invitation,
+    };
+
+    // Mark the invitation as declined.
+    invitation.status = InvitationStatus::Declined;
+    match invitation.sync().await {
+        Err(e) => {
+            error!("Couldn\'t update the invitation. {}", e);
+            ApiError::Internal("decline the invitation").into()
+        }
+        Ok(_) => {
+            info!("{} declined.", invitation.id.as_ref().unwrap());
+            (
+                StatusCode::OK,
+                ResponseBody::success(invitation.to_invitation_response()),
+            )
+        }
+    }
+}
+
+/**
+ * A method to get a pending invitation sent to the user.
+ * Expired invitations are marked as expired and rejected.
+*/
+async fn get_pending_invitation(
+    user_id: &Thing,
+    invitation_id: String,
+) -> Result<Invitation, ApiError> {
+    let mut invitation = get_invitation_from_id(Id::from(invitation_id)).await?;
+
+    // Check if the invitation was sent to the user.
+    if &invitation.invitee != user_id {
+        warn!("The invitation wasn\'t sent to the user.");
+        return Err(ApiError::ForbiddenInvitation);
+    }
+
+    // Check if the invitation can still be answered.
+    if invitation.status == InvitationStatus::Pending && invitation.is_expired() {
+        invitation.status = InvitationStatus::Expired;
+        if let Err(e) = invitation.sync().await {
+            warn!("Couldn\'t expire the invitation. {}", e);
+        }
+    }
+    match invitation.status {
+        InvitationStatus::Pending => Ok(invitation),
+        InvitationStatus::Expired => Err(ApiError::InvitationExpired),
+        _ => Err(ApiError::InvitationAnswered),
+    }
+}
@@ -1,60 +1,60 @@
 // Libs
-use axum::{extract::Path, http::StatusCode, Json};
-use axum_auth::AuthBasic;
+use axum::{extract::Path, http::StatusCode};
 use surrealdb::sql::Id;
 use tracing::{error, warn};
 
+use super::api_error::ApiError;
 use super::controllers_utils::*;
-use super::response_body::ResponseBody;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::extractors::{auth_extractor::AuthUser, validated_json_extractor::ValidatedJson};
 use crate::models::{
-    media_model::Media, media_model::MediaRequest, model_trait::ModelTrait,
-    watchlist_model::Watchlist,
+    media_model::Media,
+    media_model::{MediaRequest, MediaResponse},
+    model_trait::ModelTrait,
+    watchlist_model::{Role, Watchlist},
 };
 
 // Functions
 /**
  * POST /media
- * Authorization: Basic
+ * Authorization: Bearer
  * BODY: MediaRequest
  * A method to create a new media.
 */
+#[utoipa::path(
+    post,
+    path = "/media",
+    tag = "media",
+    summary = "Create a new media.",
+    description = "A method to create a new media.",
+    request_body = MediaRequest,
+    responses(
+        (status = 201, description = "The media was created.", body = ResponseData<MediaResponse>),
+        (status = 400, description = "The request isn\'t valid or the watchlist wasn\'t found.", body = ResponseBody),
+        (status = 403, description = "The user can\'t add media to the watchlist.", body = ResponseBody),
+    )
+)]
 pub async fn post_media(
-    AuthBasic(user_auth): AuthBasic,
-    Json(new_media): Json<MediaRequest>,
+    AuthUser(user_id): AuthUser,
+    ValidatedJson(new_media): ValidatedJson<MediaRequest>,
 ) -> Response {
-    // Check if the user is valid.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(logged_user) => logged_user,
-    };
-
     // Check if the provided watchlist is valid.
     let watchlist = match Watchlist::from_id(Id::from(&new_media.watchlist)).await {
-        Ok(Some(watchlist)) => watchlist,
+        Ok(Some(watchlist)) if !watchlist.is_trashed() => watchlist,
         Err(e) => {
             warn!("Couldn\'t get the watchlist. {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ResponseBody::error("Couldn\'t create the media. Please call the admin."),
-            );
+            return ApiError::Internal("create the media").into();
         }
-        Ok(None) => {
+        Ok(_) => {
             warn!("Watchlist not found.");
-            return (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error("Watchlist not found. Check the id and try again."),
-            );
+            return ApiError::invalid_field("watchlist", "The watchlist wasn\'t found.").into();
         }
     };
 
     // Check if the user has permission to add a media to the watchlist.
-    let id = logged_user.id.as_ref().unwrap();
-    if !watchlist.is_owner(id) && !watchlist.has_member(id) {
+    if !watchlist.has_role(&user_id, Role::Editor) {
         warn!("User doesn\'t have permission to add a media to the watchlist.");
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to add a media to the watchlist."),
-        );
+        return ApiError::ForbiddenWatchlist("add media to").into();
     }
 
     // Try to synchronize the given media in the database.
@@ -62,12 +62,7 @@ pub async fn post_media(
     match new_media.sync().await {
         Err(e) => {
             warn!("Couldn\'t create the media. {}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error(
-                    "Couldn\'t create the media. Check the parameters and try again.",
-                ),
-            )
+            ApiError::InvalidParameters("create the media").into()
         }
         Ok(_) => (
             StatusCode::CREATED,
@@ -78,35 +73,39 @@ pub async fn post_media(
 
 /**
  * GET /media/{media_id}
- * Authorization: Basic
+ * Authorization: Bearer
  * A method to get an media.
 */
-pub async fn get_media(AuthBasic(user_auth): AuthBasic, Path(media_id): Path<String>) -> Response {
-    // Get the user.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(logged_user) => logged_user,
-    };
-
+#[utoipa::path(
+    get,
+    path = "/media/{media_id}",
+    tag = "media",
+    summary = "Get an media.",
+    description = "A method to get an media.",
+    params(("media_id" = String, Path, description = "The id of the media.")),
+    responses(
+        (status = 200, description = "The media was found.", body = ResponseData<MediaResponse>),
+        (status = 400, description = "The id isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user isn\'t a member of the watchlist.", body = ResponseBody),
+        (status = 404, description = "The media wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn get_media(AuthUser(user_id): AuthUser, Path(media_id): Path<String>) -> Response {
     // Try to get the media.
     let media = match get_media_from_id(Id::from(media_id)).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(media) => media,
     };
 
     // Get the media watchlist.
     let media_watchlist = match get_watchlist_from_id(media.watchlist.id.clone()).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(media_watchlist) => media_watchlist,
     };
 
     // Check if the user has permission in the watchlist.
-    let id = logged_user.id.as_ref().unwrap();
-    if !media_watchlist.is_owner(id) && !media_watchlist.has_member(id) {
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to get the media."),
-        );
+    if !media_watchlist.has_role(&user_id, Role::Viewer) {
+        return ApiError::ForbiddenMedia("get").into();
     }
 
     (
@@ -117,57 +116,76 @@ pub async fn get_media(AuthBasic(user_auth): AuthBasic, Path(media_id): Path<Str
 
 /**
  * PATCH /media/{media_id}
- * Authorization: Basic
+ * Authorization: Bearer
  * BODY: MediaRequest
  * A method to update an media.
 */
+#[utoipa::path(
+    patch,
+    path = "/media/{media_id}",
+    tag = "media",
+    summary = "Update an media.",
+    description = "A method to update an media.",
+    params(("media_id" = String, Path, description = "The id of the media.")),
+    request_body = MediaRequest,
+    responses(
+        (status = 200, description = "The media was updated.", body = ResponseData<MediaResponse>),
+        (status = 400, description = "The request isn\'t valid, or the kind can\'t have the episodes of the media.", body = ResponseBody),
+        (status = 403, description = "The user can\'t update the media.", body = ResponseBody),
+        (status = 404, description = "The media wasn\'t found.", body = ResponseBody),
+    )
+)]
 pub async fn patch_media(
-    AuthBasic(user_auth): AuthBasic,
+    AuthUser(user_id): AuthUser,
     Path(media_id): Path<String>,
-    Json(new_media): Json<MediaRequest>,
+    ValidatedJson(new_media): ValidatedJson<MediaRequest>,
 ) -> Response {
-    // Check if the user is valid.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(logged_user) => logged_user,
-    };
     // Check if the provided media is valid.
     let mut db_media = match get_media_from_id(Id::from(media_id)).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(media) => media,
     };
 
     // Check if the provided watchlist is valid.
     let watchlist_id = db_media.watchlist.clone().id;
     let db_watchlist = match get_watchlist_from_id(watchlist_id).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(media) => media,
     };
 
-    // Check if the user has permission to add the media to the watchlist.
-    let id = logged_user.id.as_ref().unwrap();
-    if !db_watchlist.is_owner(id) && !db_watchlist.has_member(id) {
-        warn!("User doesn\'t have permission to add a media to the watchlist.");
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to add a media to the watchlist."),
-        );
+    // Check if the user has permission to update the media in the watchlist.
+    if !db_watchlist.has_role(&user_id, Role::Editor) {
+        warn!("User doesn\'t have permission to update the media.");
+        return ApiError::ForbiddenMedia("update").into();
     }
 
-    // Define the content that the response doesn't have/can't modify.
+    // Check if the user has permission to move the media to the new watchlist.
     let new_media = Media::from(new_media);
+    if new_media.watchlist != db_media.watchlist {
+        let new_watchlist = match get_watchlist_from_id(new_media.watchlist.id.clone()).await {
+            Err(e) => return e.into(),
+            Ok(watchlist) => watchlist,
+        };
+        if !new_watchlist.has_role(&user_id, Role::Editor) {
+            warn!("User doesn\'t have permission to add a media to the watchlist.");
+            return ApiError::ForbiddenWatchlist("add media to").into();
+        }
+    }
+
+    // Check if the episodes would be left in a media that can't have them.
+    if db_media.progress.is_some() && !new_media.kind.is_episodic() {
+        warn!("The media still has episodes.");
+        return ApiError::MediaHasEpisodes.into();
+    }
+
+    // Define the content that the response doesn't have/can't modify.
     db_media.merge(new_media);
 
-    // Try to synchronize the media in the database.
-    match db_media.sync().await {
+    // Try to synchronize the media in the database. The watched of the series is derived from their episodes.
+    match db_media.sync_progress().await {
         Err(e) => {
             error!("Couldn\'t update the media. {}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error(
-                    "Couldn\'t update the media. Check the parameters and try again.",
-                ),
-            )
+            ApiError::InvalidParameters("update the media").into()
         }
         Ok(_) => (
             StatusCode::OK,
@@ -178,49 +196,47 @@ pub async fn patch_media(
 
 /**
  * DELETE /media/{media_id}
- * Authorization: Basic
- * A method to delete an media.
+ * Authorization: Bearer
+ * A method to move an media to the trash.
 */
-pub async fn delete_media(
-    AuthBasic(media_auth): AuthBasic,
-    Path(media_id): Path<String>,
-) -> Response {
-    // Check if the authorization is valid.
-    let logged_user = match login_user(media_auth, false).await {
-        Err(res) => return res,
-        Ok(logged_user) => logged_user,
-    };
-
+#[utoipa::path(
+    delete,
+    path = "/media/{media_id}",
+    tag = "media",
+    summary = "Move an media to the trash.",
+    description = "A method to move an media to the trash.",
+    params(("media_id" = String, Path, description = "The id of the media.")),
+    responses(
+        (status = 200, description = "The media was moved to the trash.", body = ResponseBody),
+        (status = 400, description = "The id isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user can\'t delete the media.", body = ResponseBody),
+        (status = 404, description = "The media wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn delete_media(AuthUser(user_id): AuthUser, Path(media_id): Path<String>) -> Response {
     // Check if the provided media is valid.
-    let provided_media = match get_media_from_id(Id::from(media_id)).await {
-        Err(res) => return res,
+    let mut provided_media = match get_media_from_id(Id::from(media_id)).await {
+        Err(e) => return e.into(),
         Ok(media) => media,
     };
 
     // Get the media watchlist.
     let watchlist_id = provided_media.watchlist.clone().id;
     let watchlist = match get_watchlist_from_id(watchlist_id).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(watchlist) => watchlist,
     };
 
     // Check if the user has permission in tthe watchlist.
-    let id = logged_user.id.as_ref().unwrap();
-    if !watchlist.is_owner(id) && !watchlist.has_member(id) {
+    if !watchlist.has_role(&user_id, Role::Admin) {
         warn!("User doesn\'t have permission to delete the media.");
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to delete the media."),
-        );
+        return ApiError::ForbiddenMedia("delete").into();
     }
 
-    // Try to delete the media.
-    if let Err(e) = provided_media.delete().await {
+    // Try to move the media to the trash.
+    if let Err(e) = provided_media.trash().await {
         error!("Couldn\'t delete the media. {}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            ResponseBody::error("Couldn\'t get the media. Please call the admin."),
-        );
+        return ApiError::Internal("delete the media").into();
     }
 
     (StatusCode::OK, ResponseBody::success_no_data())
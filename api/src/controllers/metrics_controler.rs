@@ -0,0 +1,32 @@
+// Libs
+use axum::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::IntoResponse,
+};
+use prometheus::TEXT_FORMAT;
+
+use crate::metrics::gather;
+
+// Functions
+/**
+ * GET /metrics
+ * A method to get the metrics of the API in the Prometheus text format.
+*/
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "probes",
+    summary = "Get the metrics of the API in the Prometheus text format.",
+    description = "A method to get the metrics of the API in the Prometheus text format.",
+    security(()),
+    responses(
+        (status = 200, description = "The metrics in the Prometheus text format.", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get_metrics() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, TEXT_FORMAT)],
+        gather().await,
+    )
+}
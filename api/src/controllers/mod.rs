@@ -1,5 +1,13 @@
+pub mod api_error;
 pub mod controllers_utils;
+pub mod episode_controler;
+pub mod health_controler;
+pub mod invitation_controler;
 pub mod media_controler;
+pub mod metrics_controler;
+pub mod openapi_controler;
 pub mod response_body;
+pub mod search_controler;
+pub mod trash_controler;
 pub mod user_controler;
 pub mod watchlist_controler;
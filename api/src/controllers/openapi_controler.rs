@@ -0,0 +1,31 @@
+// Libs
+use axum::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::IntoResponse,
+};
+
+use crate::openapi::OPENAPI_JSON;
+
+// Functions
+/**
+ * GET /openapi.json
+ * A method to get the OpenAPI spec of the API, generated from the handlers.
+*/
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    tag = "probes",
+    summary = "Get the OpenAPI spec of the API, generated from the handlers.",
+    description = "A method to get the OpenAPI spec of the API, generated from the handlers.",
+    security(()),
+    responses(
+        (status = 200, description = "The OpenAPI spec.", body = Object, content_type = "application/json"),
+    )
+)]
+pub async fn get_openapi() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, "application/json")],
+        OPENAPI_JSON.as_str(),
+    )
+}
@@ -2,22 +2,41 @@
 use axum::Json;
 use serde::Serialize;
 use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::api_error::{ApiError, FieldError};
 
 // Enums
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 enum Status {
     Success,
     Failed,
 }
 
 // Structs
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ResponseBody {
     status: Status,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     pub data: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "WATCHLIST_NOT_FOUND")]
+    pub code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<FieldError>>,
+}
+
+/**
+ * The body of the success responses with data. Only used by the spec, the handlers build it with ResponseBody::success.
+*/
+#[derive(Serialize, ToSchema)]
+#[schema(description = "The body of the success responses with data.")]
+pub struct ResponseData<T> {
+    status: Status,
+    pub data: T,
 }
 
 // Implementations
@@ -29,7 +48,9 @@ impl ResponseBody {
         Json(Self {
             status: Status::Success,
             data: Some(serde_json::to_value(data).unwrap()),
+            code: None,
             message: None,
+            details: None,
         })
     }
 
@@ -40,19 +61,22 @@ impl ResponseBody {
         Json(Self {
             status: Status::Success,
             data: None,
+            code: None,
             message: None,
+            details: None,
         })
     }
 
     /**
-     * A method to create a error response body.
-
-    */
-    pub fn error(message: &str) -> Json<Self> {
+     * A method to create a error response body, with the code, message and invalid fields of the error.
+     */
+    pub fn error(error: &ApiError) -> Json<Self> {
         Json(Self {
             status: Status::Failed,
             data: None,
-            message: Some(message.to_string()),
+            code: Some(error.code()),
+            message: Some(error.message()),
+            details: error.details(),
         })
     }
 }
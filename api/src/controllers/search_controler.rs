@@ -0,0 +1,56 @@
+// Libs
+use axum::http::StatusCode;
+use surrealdb::sql::Thing;
+use tracing::error;
+
+use super::api_error::ApiError;
+use super::controllers_utils::*;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::extractors::{auth_extractor::AuthUser, validated_query_extractor::ValidatedQuery};
+use crate::models::search_model::{
+    SearchQuery, SearchResponse, SearchResult, SEARCH_DEFAULT_LIMIT,
+};
+
+// Functions
+/**
+ * GET /search
+ * Authorization: Bearer
+ * QUERY: SearchQuery
+ * A method to search the watchlists and medias the user can access.
+*/
+#[utoipa::path(
+    get,
+    path = "/search",
+    tag = "search",
+    summary = "Search the watchlists and medias the user can access.",
+    description = "A method to search the watchlists and medias the user can access.",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "The ranked watchlists and medias.", body = ResponseData<SearchResponse>),
+        (status = 400, description = "The query or the limit isn\'t valid.", body = ResponseBody),
+    )
+)]
+pub async fn get_search(
+    AuthUser(user_id): AuthUser,
+    ValidatedQuery(query): ValidatedQuery<SearchQuery>,
+) -> Response {
+    let limit = query.limit.unwrap_or(SEARCH_DEFAULT_LIMIT);
+
+    // Get the watchlists the user can access.
+    let watchlists: Vec<Thing> = match get_all_watchlist_from_user(&user_id).await {
+        Err(e) => return e.into(),
+        Ok(watchlists) => watchlists.into_iter().filter_map(|w| w.id).collect(),
+    };
+
+    // Search in the watchlists.
+    match SearchResult::search(&query.q, &watchlists, limit).await {
+        Err(e) => {
+            error!("Couldn\'t search. {}", e);
+            ApiError::Internal("search").into()
+        }
+        Ok(result) => (
+            StatusCode::OK,
+            ResponseBody::success(result.to_search_response()),
+        ),
+    }
+}
@@ -0,0 +1,154 @@
+// Libs
+use axum::{extract::Path, http::StatusCode};
+use surrealdb::sql::{Id, Thing};
+use tracing::{error, info, warn};
+
+use super::api_error::ApiError;
+use super::controllers_utils::*;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::extractors::auth_extractor::AuthUser;
+use crate::models::{
+    media_model::Media,
+    model_trait::ModelTrait,
+    trash_model::{Trash, TrashResponse},
+    watchlist_model::{Role, Watchlist},
+};
+
+// Functions
+/**
+ * GET /trash
+ * Authorization: Bearer
+ * A method to get the trashed watchlists the user owns and the trashed medias from the watchlists the user manages.
+*/
+#[utoipa::path(
+    get,
+    path = "/trash",
+    tag = "trash",
+    summary = "Get the trashed watchlists the user owns and the trashed medias from the watchlists the user manages.",
+    description = "A method to get the trashed watchlists the user owns and the trashed medias from the watchlists the user manages.",
+    responses(
+        (status = 200, description = "The trash of the user.", body = ResponseData<TrashResponse>),
+    )
+)]
+pub async fn get_trash(AuthUser(user_id): AuthUser) -> Response {
+    match Trash::from_user(&user_id).await {
+        Err(e) => {
+            error!("Couldn\'t get the trash. {}", e);
+            ApiError::Internal("get the trash").into()
+        }
+        Ok(trash) => (
+            StatusCode::OK,
+            ResponseBody::success(trash.to_trash_response()),
+        ),
+    }
+}
+
+/**
+ * POST /trash/{trash_id}/restore
+ * Authorization: Bearer
+ * A method to restore a watchlist or a media from the trash.
+*/
+#[utoipa::path(
+    post,
+    path = "/trash/{trash_id}/restore",
+    tag = "trash",
+    summary = "Restore a watchlist or a media from the trash.",
+    description = "A method to restore a watchlist or a media from the trash.",
+    params(("trash_id" = String, Path, description = "The id of the trashed watchlist or media.")),
+    responses(
+        (status = 200, description = "The restored watchlist or media.", body = ResponseBody),
+        (status = 400, description = "The id isn\'t valid or the watchlist of the media is trashed.", body = ResponseBody),
+        (status = 403, description = "The user can\'t restore it.", body = ResponseBody),
+        (status = 404, description = "Nothing with the id was found in the trash.", body = ResponseBody),
+    )
+)]
+pub async fn post_trash_restore(
+    AuthUser(user_id): AuthUser,
+    Path(trash_id): Path<String>,
+) -> Response {
+    let trash_id = Id::from(trash_id);
+
+    // Check if it's a trashed watchlist.
+    match Watchlist::from_id(trash_id.clone()).await {
+        Err(e) => {
+            error!("Couldn\'t get the watchlist. {}", e);
+            return ApiError::Internal("restore from the trash").into();
+        }
+        Ok(Some(watchlist)) if watchlist.is_trashed() => {
+            return restore_watchlist(&user_id, watchlist).await
+        }
+        Ok(_) => (),
+    }
+
+    // Check if it's a trashed media.
+    match Media::from_id(trash_id).await {
+        Err(e) => {
+            error!("Couldn\'t get the media. {}", e);
+            ApiError::Internal("restore from the trash").into()
+        }
+        Ok(Some(media)) if media.is_trashed() => restore_media(&user_id, media).await,
+        Ok(_) => ApiError::TrashNotFound.into(),
+    }
+}
+
+/**
+ * A method to restore a trashed watchlist. Only the owner can restore it.
+*/
+async fn restore_watchlist(user_id: &Thing, mut watchlist: Watchlist) -> Response {
+    if !watchlist.is_owner(user_id) {
+        warn!("User doesn\'t have permission to restore the watchlist.");
+        return ApiError::ForbiddenWatchlist("restore").into();
+    }
+
+    match watchlist.restore().await {
+        Err(e) => {
+            error!("Couldn\'t restore the watchlist. {}", e);
+            ApiError::Internal("restore the watchlist").into()
+        }
+        Ok(_) => {
+            info!("{} restored.", watchlist.id.as_ref().unwrap());
+            (
+                StatusCode::OK,
+                ResponseBody::success(watchlist.to_watchlist_response()),
+            )
+        }
+    }
+}
+
+/**
+ * A method to restore a trashed media. Only the owner and the admins of its watchlist can restore it.
+*/
+async fn restore_media(user_id: &Thing, mut media: Media) -> Response {
+    // Get the media watchlist.
+    let watchlist = match Watchlist::from_id(media.watchlist.id.clone()).await {
+        Err(e) => {
+            error!("Couldn\'t get the watchlist. {}", e);
+            return ApiError::Internal("restore the media").into();
+        }
+        Ok(None) => return ApiError::WatchlistNotFound.into(),
+        Ok(Some(watchlist)) => watchlist,
+    };
+
+    // Check if the user has permission in the watchlist.
+    if !watchlist.has_role(user_id, Role::Admin) {
+        warn!("User doesn\'t have permission to restore the media.");
+        return ApiError::ForbiddenMedia("restore").into();
+    }
+    if watchlist.is_trashed() {
+        return ApiError::WatchlistTrashed.into();
+    }
+
+    match media.restore().await {
+        Err(e) => {
+            error!("Couldn\'t restore the media. {}", e);
+            ApiError::Internal("restore the media").into()
+        }
+        Ok(_) => {
+            info!("{} restored.", media.id.as_ref().unwrap());
+            (
+                StatusCode::OK,
+                ResponseBody::success(media.to_media_response()),
+            )
+        }
+    }
+}
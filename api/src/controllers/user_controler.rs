@@ -1,16 +1,21 @@
 // Libs
-use axum::{extract::Path, http::StatusCode, Json};
-use axum_auth::AuthBasic;
+use axum::{extract::Path, http::StatusCode};
 use surrealdb::sql::Id;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
+use super::api_error::ApiError;
 use super::controllers_utils::*;
-use super::response_body::ResponseBody;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::extractors::{
+    auth_extractor::{AuthCredentials, AuthToken, AuthUser},
+    validated_json_extractor::ValidatedJson,
+};
 use crate::models::{
     model_trait::ModelTrait,
-    user_model::{User, UserRequest},
+    user_model::{User, UserLoginResponse, UserRequest, UserResponse},
 };
-use crate::security::is_valid_field;
+use crate::security::{validate_token, verify_password, TokenKind};
+use crate::validation::username_key;
 
 // Functions
 /**
@@ -18,22 +23,28 @@ use crate::security::is_valid_field;
  * BODY: UserRequest
  * A method to create a new user.
 */
-pub async fn post_user(Json(new_user): Json<UserRequest>) -> Response {
-    // Check if the username is valid.
-    if !is_valid_field(&new_user.username, 20) {
-        return (
-            StatusCode::BAD_REQUEST,
-            ResponseBody::error("The username is invalid. Check the parameters and try again."),
-        );
+#[utoipa::path(
+    post,
+    path = "/user",
+    tag = "user",
+    summary = "Create a new user.",
+    description = "A method to create a new user.",
+    request_body = UserRequest,
+    security(()),
+    responses(
+        (status = 201, description = "The user was created.", body = ResponseData<UserResponse>),
+        (status = 400, description = "The username or the password isn\'t valid, or the username is taken.", body = ResponseBody),
+    )
+)]
+pub async fn post_user(ValidatedJson(new_user): ValidatedJson<UserRequest>) -> Response {
+    if let Err(e) = check_username_limit(&new_user.username) {
+        return e.into();
     }
 
     // Check if the username already exists.
     if let Ok(Some(_)) = User::from_username(&new_user.username).await {
         warn!("Username already exists.");
-        return (
-            StatusCode::BAD_REQUEST,
-            ResponseBody::error("The username already exists. Try other."),
-        );
+        return ApiError::UsernameTaken.into();
     }
 
     let mut new_user = User::from(new_user);
@@ -41,12 +52,7 @@ pub async fn post_user(Json(new_user): Json<UserRequest>) -> Response {
     match new_user.sync().await {
         Err(e) => {
             warn!("Couldn\'t create the user. {}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error(
-                    "Couldn\'t create the user. Check the parameters and try again.",
-                ),
-            )
+            ApiError::InvalidParameters("create the user").into()
         }
         Ok(_) => (
             StatusCode::CREATED,
@@ -57,40 +63,52 @@ pub async fn post_user(Json(new_user): Json<UserRequest>) -> Response {
 
 /**
  * PATCH /user/
- * Authorization: Basic
+ * Authorization: Bearer
  * BODY: UserRequest
  * A method to update an user.
 */
+#[utoipa::path(
+    patch,
+    path = "/user",
+    tag = "user",
+    summary = "Update an user.",
+    description = "A method to update an user. A new password revokes the current tokens, so the user needs to login again.",
+    request_body = UserRequest,
+    responses(
+        (status = 200, description = "The user was updated.", body = ResponseData<UserResponse>),
+        (status = 400, description = "The username or the password isn\'t valid, or the username is taken.", body = ResponseBody),
+    )
+)]
 pub async fn patch_user(
-    AuthBasic(user_auth): AuthBasic,
-    Json(new_user_content): Json<UserRequest>,
+    AuthUser(user_id): AuthUser,
+    ValidatedJson(new_user_content): ValidatedJson<UserRequest>,
 ) -> Response {
-    // Check if the authorization is valid.
-    let mut logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
+    // Get the logged user.
+    let mut logged_user = match get_user_from_id(user_id.id).await {
+        Err(ApiError::UserNotFound) => return ApiError::InvalidToken.into(),
+        Err(e) => return e.into(),
         Ok(user) => user,
     };
 
-    // Check if the provided username is different from the database.
-    if new_user_content.username != logged_user.username {
-        // Check if the username is valid.
-        if !is_valid_field(&new_user_content.username, 20) {
-            return (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error("The username is invalid. Check the parameters and try again."),
-            );
+    // Check if the provided username has other key. Changing only its case keeps the key.
+    if username_key(&new_user_content.username) != logged_user.username_key {
+        if let Err(e) = check_username_limit(&new_user_content.username) {
+            return e.into();
         }
 
         // Check if the username already exists.
         if let Ok(Some(_)) = User::from_username(&new_user_content.username).await {
             warn!("Username already exists.");
-            return (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error("The username already exists. Try other."),
-            );
+            return ApiError::UsernameTaken.into();
         }
     }
 
+    // A new password revokes the current tokens.
+    if !verify_password(new_user_content.password.as_bytes(), &logged_user.password) {
+        info!("The password changed, the tokens are revoked.");
+        logged_user.token_version += 1;
+    }
+
     // Define the content that the response doesn't have/can't modify.
     let new_user_content = User::from(new_user_content);
     logged_user.merge(new_user_content);
@@ -99,12 +117,7 @@ pub async fn patch_user(
     match logged_user.sync().await {
         Err(e) => {
             error!("Couldn\'t update the user. {}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error(
-                    "Couldn\'t update the user. Check the parameters and try again.",
-                ),
-            )
+            ApiError::InvalidParameters("update the user").into()
         }
         Ok(_) => (
             StatusCode::OK,
@@ -115,23 +128,31 @@ pub async fn patch_user(
 
 /**
  * DELETE /user/
- * Authorization: Basic
+ * Authorization: Bearer
  * A method to delete an user.
 */
-pub async fn delete_user(AuthBasic(user_auth): AuthBasic) -> Response {
-    // Check if the authorization is valid.
-    let provided_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
+#[utoipa::path(
+    delete,
+    path = "/user",
+    tag = "user",
+    summary = "Delete an user.",
+    description = "A method to delete an user.",
+    responses(
+        (status = 200, description = "The user, its watchlists and its invitations were deleted.", body = ResponseBody),
+    )
+)]
+pub async fn delete_user(AuthUser(user_id): AuthUser) -> Response {
+    // Get the logged user.
+    let provided_user = match get_user_from_id(user_id.id).await {
+        Err(ApiError::UserNotFound) => return ApiError::InvalidToken.into(),
+        Err(e) => return e.into(),
         Ok(user) => user,
     };
 
     // Try to delete the user.
     if let Err(e) = provided_user.delete().await {
         error!("Couldn\'t delete the user. {}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            ResponseBody::error("Couldn\'t get the user. Please call the admin."),
-        );
+        return ApiError::Internal("delete the user").into();
     }
 
     (StatusCode::OK, ResponseBody::success_no_data())
@@ -141,10 +162,24 @@ pub async fn delete_user(AuthBasic(user_auth): AuthBasic) -> Response {
  * GET /user/{user_id}
  * A method to get an user.
 */
+#[utoipa::path(
+    get,
+    path = "/user/{user_id}",
+    tag = "user",
+    summary = "Get an user.",
+    description = "A method to get an user.",
+    params(("user_id" = String, Path, description = "The id of the user.")),
+    security(()),
+    responses(
+        (status = 200, description = "The user was found.", body = ResponseData<UserResponse>),
+        (status = 400, description = "The id isn\'t valid.", body = ResponseBody),
+        (status = 404, description = "The user wasn\'t found.", body = ResponseBody),
+    )
+)]
 pub async fn get_user(Path(user_id): Path<String>) -> Response {
     // Try to get the user.
     match get_user_from_id(Id::from(user_id)).await {
-        Err(response) => response,
+        Err(e) => e.into(),
         Ok(user) => (
             StatusCode::OK,
             ResponseBody::success(user.to_user_response()),
@@ -155,15 +190,68 @@ pub async fn get_user(Path(user_id): Path<String>) -> Response {
 /**
  * POST /user/login
  * Authorization: Basic
- * A method to login an user. Uses its username and password. It's needed to get the user id.
+ * A method to login an user. Uses its username and password. Returns the user and a new token pair.
 */
-pub async fn post_user_login(AuthBasic(user_auth): AuthBasic) -> Response {
+#[utoipa::path(
+    post,
+    path = "/user/login",
+    tag = "user",
+    summary = "Login an user.",
+    description = "A method to login an user. Uses its username and password. Returns the user and a new token pair.",
+    security(("basic_auth" = [])),
+    responses(
+        (status = 200, description = "The user was logged in.", body = ResponseData<UserLoginResponse>),
+        (status = 401, description = "The username or the password is wrong.", body = ResponseBody),
+        (status = 429, description = "Too many requests or failed logins. The login is locked for a while.", body = ResponseBody,
+            headers(("Retry-After" = u64, description = "The seconds to wait before trying again."))),
+    )
+)]
+pub async fn post_user_login(AuthCredentials(user_auth): AuthCredentials) -> Response {
     // Try to login the user.
-    match login_user(user_auth, true).await {
-        Err(response) => response,
+    match login_user(user_auth).await {
+        Err(e) => e.into(),
         Ok(user) => (
             StatusCode::OK,
-            ResponseBody::success(user.to_user_response()),
+            ResponseBody::success(user.to_user_login_response()),
+        ),
+    }
+}
+
+/**
+ * POST /user/refresh
+ * Authorization: Bearer (refresh token)
+ * A method to get a new token pair using a refresh token.
+*/
+#[utoipa::path(
+    post,
+    path = "/user/refresh",
+    tag = "user",
+    summary = "Get a new token pair using a refresh token.",
+    description = "A method to get a new token pair using a refresh token.",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "A new token pair was generated.", body = ResponseData<UserLoginResponse>),
+        (status = 401, description = "The refresh token is missing or invalid.", body = ResponseBody),
+    )
+)]
+pub async fn post_user_refresh(AuthToken(token): AuthToken) -> Response {
+    // Check the refresh token.
+    let claims = match validate_token(&token, TokenKind::Refresh) {
+        None => return ApiError::InvalidToken.into(),
+        Some(claims) => claims,
+    };
+
+    // Check if the user still exists and the token wasn't revoked.
+    match get_user_from_id(Id::from(claims.sub)).await {
+        Err(ApiError::UserNotFound) => ApiError::InvalidToken.into(),
+        Err(e) => e.into(),
+        Ok(user) if user.token_version != claims.ver => {
+            warn!("The refresh token was revoked.");
+            ApiError::InvalidToken.into()
+        }
+        Ok(user) => (
+            StatusCode::OK,
+            ResponseBody::success(user.to_user_login_response()),
         ),
     }
 }
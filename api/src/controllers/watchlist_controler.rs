@@ -1,54 +1,61 @@
 // Libs
-use axum::{extract::Path, http::StatusCode, Json};
-use axum_auth::AuthBasic;
-use surrealdb::sql::Id;
+use axum::{extract::Path, http::StatusCode};
+use surrealdb::sql::{Id, Thing};
 use tracing::{error, info, warn};
 
+use super::api_error::ApiError;
 use super::controllers_utils::*;
-use super::response_body::ResponseBody;
+use super::response_body::{ResponseBody, ResponseData};
+use crate::extractors::{
+    auth_extractor::AuthUser, validated_json_extractor::ValidatedJson,
+    validated_query_extractor::ValidatedQuery,
+};
 use crate::models::{
-    media_model::MediaResponse,
+    media_model::{MediaCursor, MediaPageResponse, MediaQuery, MEDIA_PAGE_DEFAULT_LIMIT},
     model_trait::ModelTrait,
-    watchlist_model::{Watchlist, WatchlistRequest, WatchlistResponse},
+    watchlist_model::{
+        Role, Watchlist, WatchlistRequest, WatchlistResponse, WatchlistTransferRequest,
+    },
 };
 
 // Functions
 /**
  * POST /watchlist
- * Authorization: Basic
+ * Authorization: Bearer
  * BODY: WatchlistRequest
  * A method to create a new watchlist.
 */
+#[utoipa::path(
+    post,
+    path = "/watchlist",
+    tag = "watchlist",
+    summary = "Create a new watchlist.",
+    description = "A method to create a new watchlist.",
+    request_body = WatchlistRequest,
+    responses(
+        (status = 201, description = "The watchlist was created.", body = ResponseData<WatchlistResponse>),
+        (status = 400, description = "The request isn\'t valid.", body = ResponseBody),
+    )
+)]
 pub async fn post_watchlist(
-    AuthBasic(user_auth): AuthBasic,
-    Json(watchlist): Json<WatchlistRequest>,
+    AuthUser(user_id): AuthUser,
+    ValidatedJson(watchlist): ValidatedJson<WatchlistRequest>,
 ) -> Response {
-    // Get the user and make it the owner of the watchlist.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(logged_user) => logged_user,
-    };
-
-    // Check if all provided members are valid.
-    if let Err(res) = are_members_valid(logged_user.id.as_ref().unwrap(), &watchlist.members).await
-    {
-        return res;
+    // Check if all provided members are valid. A new watchlist has no members to keep.
+    let members = watchlist.members.as_deref().unwrap_or_default();
+    if let Err(e) = are_members_valid(&[], members) {
+        return e.into();
     }
 
     // Convert the watchlist request to a watchlist.
     let mut watchlist = Watchlist::from(watchlist);
-    watchlist.owner = logged_user.id;
+    watchlist.owner = Some(user_id);
 
     // Try to synchronize the given watchlist in the database.
     match watchlist.sync().await {
         Err(e) => {
             warn!("Couldn\'t create the watchlist. {}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error(
-                    "Couldn\'t create the watchlist. Check the parameters and try again.",
-                ),
-            )
+            ApiError::InvalidParameters("create the watchlist").into()
         }
         Ok(_) => (
             StatusCode::CREATED,
@@ -59,19 +66,23 @@ pub async fn post_watchlist(
 
 /**
  * GET /watchlists
- * Authorization: Basic
+ * Authorization: Bearer
  * A method to get all the watchlist from the user.
 */
-pub async fn get_watchlists(AuthBasic(user_auth): AuthBasic) -> Response {
-    // Check if the authorization is valid.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(logged_user) => logged_user,
-    };
-
+#[utoipa::path(
+    get,
+    path = "/watchlist",
+    tag = "watchlist",
+    summary = "Get all the watchlist from the user.",
+    description = "A method to get all the watchlist from the user.",
+    responses(
+        (status = 200, description = "The watchlists the user owns or is a member of.", body = ResponseData<Vec<WatchlistResponse>>),
+    )
+)]
+pub async fn get_watchlists(AuthUser(user_id): AuthUser) -> Response {
     // Get all the watchlists from the user.
-    let watchlists = match get_all_watchlist_from_user(&logged_user).await {
-        Err(res) => return res,
+    let watchlists = match get_all_watchlist_from_user(&user_id).await {
+        Err(e) => return e.into(),
         Ok(watchlists) => watchlists,
     };
 
@@ -84,32 +95,36 @@ pub async fn get_watchlists(AuthBasic(user_auth): AuthBasic) -> Response {
 
 /**
  * GET /watchlist/{watchlist_id}
- * Authorization: Basic
+ * Authorization: Bearer
  * A method to get an watchlist.
 */
+#[utoipa::path(
+    get,
+    path = "/watchlist/{watchlist_id}",
+    tag = "watchlist",
+    summary = "Get an watchlist.",
+    description = "A method to get an watchlist.",
+    params(("watchlist_id" = String, Path, description = "The id of the watchlist.")),
+    responses(
+        (status = 200, description = "The watchlist was found.", body = ResponseData<WatchlistResponse>),
+        (status = 400, description = "The id isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user isn\'t a member of the watchlist.", body = ResponseBody),
+        (status = 404, description = "The watchlist wasn\'t found.", body = ResponseBody),
+    )
+)]
 pub async fn get_watchlist(
-    AuthBasic(user_auth): AuthBasic,
+    AuthUser(user_id): AuthUser,
     Path(watchlist_id): Path<String>,
 ) -> Response {
-    // Check if the authorization is valid.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(user) => user,
-    };
-
     // Get the watchlist.
     let watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(watchlist) => watchlist,
     };
 
     // Check if the user is the owner or is a members of the watchlist.
-    let id = logged_user.id.as_ref().unwrap();
-    if !watchlist.is_owner(id) && !watchlist.has_member(id) {
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to access this watchlist."),
-        );
+    if !watchlist.has_role(&user_id, Role::Viewer) {
+        return ApiError::ForbiddenWatchlist("access").into();
     }
 
     (
@@ -120,53 +135,65 @@ pub async fn get_watchlist(
 
 /**
  * PATCH /watchlist/{watchlist_id}
- * Authorization: Basic
+ * Authorization: Bearer
  * BODY: WatchlistRequest
  * A method to update an watchlist.
 */
+#[utoipa::path(
+    patch,
+    path = "/watchlist/{watchlist_id}",
+    tag = "watchlist",
+    summary = "Update an watchlist.",
+    description = "A method to update an watchlist.",
+    params(("watchlist_id" = String, Path, description = "The id of the watchlist.")),
+    request_body = WatchlistRequest,
+    responses(
+        (status = 200, description = "The watchlist was updated.", body = ResponseData<WatchlistResponse>),
+        (status = 400, description = "The request isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user can\'t update the watchlist.", body = ResponseBody),
+        (status = 404, description = "The watchlist wasn\'t found.", body = ResponseBody),
+    )
+)]
 pub async fn patch_watchlist(
-    AuthBasic(user_auth): AuthBasic,
+    AuthUser(user_id): AuthUser,
     Path(watchlist_id): Path<String>,
-    Json(new_watchlist): Json<WatchlistRequest>,
+    ValidatedJson(new_watchlist): ValidatedJson<WatchlistRequest>,
 ) -> Response {
-    // Check if the authorization is valid.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(logged_user) => logged_user,
-    };
-
     // Try to get the watchlist.
     let mut db_watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(watchlist) => watchlist,
     };
 
-    // Check if the user is the owner of the watchlist.
-    if !db_watchlist.is_owner(logged_user.id.as_ref().unwrap()) {
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to update this watchlist."),
-        );
+    // Check if the user is the owner or an admin of the watchlist.
+    if !db_watchlist.has_role(&user_id, Role::Admin) {
+        return ApiError::ForbiddenWatchlist("update").into();
     }
 
-    // Check if all provided members are valid.
-    if let Err(res) = are_members_valid(&logged_user.id.unwrap(), &new_watchlist.members).await {
-        return res;
+    // Check if all provided members are valid. Without members, the current ones are kept.
+    let members = new_watchlist.members.as_deref().unwrap_or_default();
+    if let Err(e) = are_members_valid(&db_watchlist.members, members) {
+        return e.into();
+    }
+
+    // Check if the user can manage the provided members.
+    let keep_members = new_watchlist.members.is_none();
+    let mut new_watchlist = Watchlist::from(new_watchlist);
+    if keep_members {
+        new_watchlist.members = db_watchlist.members.clone();
+    }
+    if let Err(e) = can_change_members(&db_watchlist, &user_id, &new_watchlist.members) {
+        return e.into();
     }
 
     // Define the content that the response doesn't have/can't modify.
-    db_watchlist.merge(Watchlist::from(new_watchlist));
+    db_watchlist.merge(new_watchlist);
 
     // Try to synchronize the watchlist in the database.
     match db_watchlist.sync().await {
         Err(e) => {
             error!("Couldn\'t update the watchlist. {}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                ResponseBody::error(
-                    "Couldn\'t update the watchlist. Check the parameters and try again.",
-                ),
-            )
+            ApiError::InvalidParameters("update the watchlist").into()
         }
         Ok(_) => {
             info!(
@@ -183,87 +210,219 @@ pub async fn patch_watchlist(
 
 /**
  * DELETE /watchlist/{watchlist_id}
- * Authorization: Basic
- * A method to delete an watchlist.
+ * Authorization: Bearer
+ * A method to move an watchlist to the trash.
 */
+#[utoipa::path(
+    delete,
+    path = "/watchlist/{watchlist_id}",
+    tag = "watchlist",
+    summary = "Move an watchlist to the trash.",
+    description = "A method to move an watchlist to the trash.",
+    params(("watchlist_id" = String, Path, description = "The id of the watchlist.")),
+    responses(
+        (status = 200, description = "The watchlist was moved to the trash.", body = ResponseBody),
+        (status = 400, description = "The id isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user isn\'t the owner of the watchlist.", body = ResponseBody),
+        (status = 404, description = "The watchlist wasn\'t found.", body = ResponseBody),
+    )
+)]
 pub async fn delete_watchlist(
-    AuthBasic(watchlist_auth): AuthBasic,
+    AuthUser(user_id): AuthUser,
     Path(watchlist_id): Path<String>,
 ) -> Response {
-    // Check if the authorization is valid.
-    let logged_user = match login_user(watchlist_auth, false).await {
-        Err(res) => return res,
-        Ok(watchlist) => watchlist,
-    };
-
     // Try to get the watchlist.
-    let db_watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
-        Err(res) => return res,
+    let mut db_watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
+        Err(e) => return e.into(),
         Ok(watchlist) => watchlist,
     };
 
     // Check if the user is the owner of the watchlist.
-    if !db_watchlist.is_owner(logged_user.id.as_ref().unwrap()) {
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to delete this watchlist."),
-        );
+    if !db_watchlist.is_owner(&user_id) {
+        return ApiError::ForbiddenWatchlist("delete").into();
     }
 
-    // Try to delete the watchlist.
+    // Try to move the watchlist to the trash.
     let watchlist_id = db_watchlist.id.clone().unwrap();
-    if let Err(e) = db_watchlist.delete().await {
+    if let Err(e) = db_watchlist.trash().await {
         error!("Couldn\'t delete the watchlist. {}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            ResponseBody::error("Couldn\'t get the watchlist. Please contact the admin."),
-        );
+        return ApiError::Internal("delete the watchlist").into();
+    }
+
+    info!("The {} was successfully moved to the trash.", watchlist_id);
+    (StatusCode::OK, ResponseBody::success_no_data())
+}
+
+/**
+ * POST /watchlist/{watchlist_id}/leave
+ * Authorization: Bearer
+ * A method to remove the user from the watchlist members.
+*/
+#[utoipa::path(
+    post,
+    path = "/watchlist/{watchlist_id}/leave",
+    tag = "watchlist",
+    summary = "Remove the user from the watchlist members.",
+    description = "A method to remove the user from the watchlist members.",
+    params(("watchlist_id" = String, Path, description = "The id of the watchlist.")),
+    responses(
+        (status = 200, description = "The user left the watchlist.", body = ResponseBody),
+        (status = 400, description = "The id isn\'t valid or the user is the owner.", body = ResponseBody),
+        (status = 403, description = "The user isn\'t a member of the watchlist.", body = ResponseBody),
+        (status = 404, description = "The watchlist wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn post_watchlist_leave(
+    AuthUser(user_id): AuthUser,
+    Path(watchlist_id): Path<String>,
+) -> Response {
+    // Get the watchlist.
+    let mut db_watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
+        Err(e) => return e.into(),
+        Ok(watchlist) => watchlist,
+    };
+
+    // Check if the user can leave the watchlist.
+    if db_watchlist.is_owner(&user_id) {
+        return ApiError::OwnerCantLeave.into();
+    }
+    if !db_watchlist.remove_member(&user_id) {
+        return ApiError::NotAMember.into();
     }
 
-    info!("The {} was successfully deleted.", watchlist_id);
+    // Try to synchronize the watchlist in the database.
+    if let Err(e) = db_watchlist.sync().await {
+        error!("Couldn\'t leave the watchlist. {}", e);
+        return ApiError::Internal("leave the watchlist").into();
+    }
+
+    info!("{} left {}.", user_id, db_watchlist.id.as_ref().unwrap());
     (StatusCode::OK, ResponseBody::success_no_data())
 }
 
+/**
+ * POST /watchlist/{watchlist_id}/transfer
+ * Authorization: Bearer
+ * BODY: WatchlistTransferRequest
+ * A method to transfer the watchlist to one of its members. The old owner becomes an admin.
+*/
+#[utoipa::path(
+    post,
+    path = "/watchlist/{watchlist_id}/transfer",
+    tag = "watchlist",
+    summary = "Transfer the watchlist to one of its members.",
+    description = "A method to transfer the watchlist to one of its members. The old owner becomes an admin.",
+    params(("watchlist_id" = String, Path, description = "The id of the watchlist.")),
+    request_body = WatchlistTransferRequest,
+    responses(
+        (status = 200, description = "The watchlist was transferred.", body = ResponseData<WatchlistResponse>),
+        (status = 400, description = "The new owner isn\'t a member of the watchlist.", body = ResponseBody),
+        (status = 403, description = "The user isn\'t the owner of the watchlist.", body = ResponseBody),
+        (status = 404, description = "The watchlist wasn\'t found.", body = ResponseBody),
+    )
+)]
+pub async fn post_watchlist_transfer(
+    AuthUser(user_id): AuthUser,
+    Path(watchlist_id): Path<String>,
+    ValidatedJson(transfer): ValidatedJson<WatchlistTransferRequest>,
+) -> Response {
+    // Get the watchlist.
+    let mut db_watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
+        Err(e) => return e.into(),
+        Ok(watchlist) => watchlist,
+    };
+
+    // Check if the user is the owner of the watchlist.
+    if !db_watchlist.is_owner(&user_id) {
+        return ApiError::ForbiddenWatchlist("transfer").into();
+    }
+
+    // Check if the new owner is a member.
+    let new_owner = Thing {
+        id: Id::from(transfer.new_owner),
+        tb: String::from("user"),
+    };
+    if db_watchlist.get_role(&new_owner).is_none() {
+        return ApiError::NewOwnerNotMember.into();
+    }
+
+    // Try to synchronize the watchlist in the database.
+    db_watchlist.transfer(new_owner);
+    match db_watchlist.sync().await {
+        Err(e) => {
+            error!("Couldn\'t transfer the watchlist. {}", e);
+            ApiError::Internal("transfer the watchlist").into()
+        }
+        Ok(_) => {
+            info!(
+                "{} transferred to {}.",
+                db_watchlist.id.as_ref().unwrap(),
+                db_watchlist.owner.as_ref().unwrap()
+            );
+            (
+                StatusCode::OK,
+                ResponseBody::success(db_watchlist.to_watchlist_response()),
+            )
+        }
+    }
+}
+
 /**
  * GET /watchlist/{watchlist_id}/media
- * Authorization: Basic
- * A method to get an watchlist.
+ * Authorization: Bearer
+ * QUERY: MediaQuery
+ * A method to get a page of media from an watchlist.
 */
+#[utoipa::path(
+    get,
+    path = "/watchlist/{watchlist_id}/media",
+    tag = "watchlist",
+    summary = "Get a page of media from an watchlist.",
+    description = "A method to get a page of media from an watchlist.",
+    params(("watchlist_id" = String, Path, description = "The id of the watchlist."), MediaQuery),
+    responses(
+        (status = 200, description = "A page of media from the watchlist.", body = ResponseData<MediaPageResponse>),
+        (status = 400, description = "The id, the cursor or the limit isn\'t valid.", body = ResponseBody),
+        (status = 403, description = "The user isn\'t a member of the watchlist.", body = ResponseBody),
+        (status = 404, description = "The watchlist wasn\'t found.", body = ResponseBody),
+    )
+)]
 pub async fn get_watchlist_medias(
-    AuthBasic(user_auth): AuthBasic,
+    AuthUser(user_id): AuthUser,
     Path(watchlist_id): Path<String>,
+    ValidatedQuery(query): ValidatedQuery<MediaQuery>,
 ) -> Response {
-    // Check if the authorization is valid.
-    let logged_user = match login_user(user_auth, false).await {
-        Err(res) => return res,
-        Ok(user) => user,
+    // Check if the cursor is valid.
+    let limit = query.limit.unwrap_or(MEDIA_PAGE_DEFAULT_LIMIT);
+    let cursor = match query.cursor.as_deref().map(MediaCursor::decode) {
+        None => None,
+        Some(Some(cursor)) if cursor.sort == query.sort => Some(cursor),
+        Some(_) => return ApiError::invalid_field("cursor", "The cursor is invalid.").into(),
     };
 
     // Get the watchlist.
     let watchlist = match get_watchlist_from_id(Id::from(watchlist_id)).await {
-        Err(res) => return res,
+        Err(e) => return e.into(),
         Ok(watchlist) => watchlist,
     };
 
     // Check if the user is the owner or is a members of the watchlist.
-    let id = logged_user.id.as_ref().unwrap();
-    if !watchlist.is_owner(id) && !watchlist.has_member(id) {
-        return (
-            StatusCode::FORBIDDEN,
-            ResponseBody::error("You don\'t have permission to access this watchlist."),
-        );
+    if !watchlist.has_role(&user_id, Role::Viewer) {
+        return ApiError::ForbiddenWatchlist("access").into();
     }
 
     // Get the medias from the watchlist.
-    match watchlist.get_media().await {
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            ResponseBody::error("Couldn\'t get the media. Please contact the admin."),
-        ),
-        Ok(medias) => {
+    match watchlist.get_media(&query, cursor.as_ref(), limit).await {
+        Err(e) => {
+            error!("Couldn\'t get the media. {}", e);
+            ApiError::Internal("get the media").into()
+        }
+        Ok(page) => {
             info!("The medias were successfully retrieved.");
-            let medias: Vec<MediaResponse> = medias.iter().map(|m| m.to_media_response()).collect();
-            (StatusCode::OK, ResponseBody::success(medias))
+            (
+                StatusCode::OK,
+                ResponseBody::success(page.to_media_page_response()),
+            )
         }
     }
 }
@@ -1,55 +1,242 @@
 // Libs
-use std::env::var;
+use std::{
+    sync::atomic::{AtomicU8, Ordering},
+    time::{Duration, Instant},
+};
 
 use once_cell::sync::Lazy;
-use surrealdb::{
-    engine::remote::ws::{Client, Ws},
-    opt::auth::Root,
-    Surreal,
-};
-use tracing::info;
+use serde::{Deserialize, Serialize};
+use surrealdb::{engine::any::Any, error::Api, opt::auth::Root, Surreal};
+use tracing::{error, info, warn};
 
-use crate::models::{
-    // media_model::Media,
-    model_trait::ModelTrait,
-    user_model::User,
-    watchlist_model::Watchlist,
-};
+use crate::config::duration_secs;
+use crate::migrations::run_migrations;
 
 // Data
-pub static DATABASE: Lazy<Surreal<Client>> = Lazy::new(Surreal::init);
+pub static DATABASE: Lazy<Surreal<Any>> = Lazy::new(Surreal::init);
+static DATABASE_STATE: AtomicU8 = AtomicU8::new(DatabaseState::Connecting as u8);
+static HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Enums
+/**
+ * The engine used to store the data, chosen by the scheme of the database URI.
+ * remote: ws://host:port or wss://host:port. A URI without scheme is also remote.
+ * memory: mem://. The data is lost when the API stops.
+ * file: file://path or rocksdb://path. Needs the `kv-rocksdb` feature.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DatabaseEngine {
+    Remote,
+    Memory,
+    File,
+}
+
+/**
+ * The state of the connection to the database.
+ * connecting: the API didn't connect yet.
+ * connected: the last health check succeeded.
+ * degraded: the connection was lost. The supervisor is reconnecting.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DatabaseState {
+    Connecting,
+    Connected,
+    Degraded,
+}
+
+// Structs
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pass: Option<String>,
+    pub namespace: String,
+    pub name: String,
+    #[serde(rename = "connect_timeout_secs", with = "duration_secs")]
+    pub connect_timeout: Duration,
+    #[serde(rename = "health_check_interval_secs", with = "duration_secs")]
+    pub health_check_interval: Duration,
+    #[serde(rename = "reconnect_max_backoff_secs", with = "duration_secs")]
+    pub reconnect_max_backoff: Duration,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OrphanReport {
+    pub watchlists: usize,
+    pub members: usize,
+    pub medias: usize,
+    pub episodes: usize,
+    pub invitations: usize,
+}
+
+// Implementations
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            uri: None,
+            user: None,
+            pass: None,
+            namespace: String::from("watchlist"),
+            name: String::from("api"),
+            connect_timeout: Duration::from_secs(60),
+            health_check_interval: Duration::from_secs(5),
+            reconnect_max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /**
+     * A method to check if the configuration values are coherent.
+     * The user and the password are only needed by the remote engine.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        let uri = match &self.uri {
+            None => return Err(String::from("The database.uri must be defined.")),
+            Some(uri) => uri,
+        };
+        if DatabaseEngine::from_uri(uri).0 == DatabaseEngine::Remote
+            && (self.user.is_none() || self.pass.is_none())
+        {
+            return Err(String::from(
+                "The database.user and database.pass must be defined to use a remote database.",
+            ));
+        }
+        if self.namespace.is_empty() || self.name.is_empty() {
+            return Err(String::from(
+                "The database.namespace and database.name can\'t be empty.",
+            ));
+        }
+        if self.health_check_interval.is_zero() || self.reconnect_max_backoff.is_zero() {
+            return Err(String::from(
+                "The database.health_check_interval_secs and database.reconnect_max_backoff_secs must be greater than 0.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl DatabaseEngine {
+    /**
+     * A method to get the engine from the URI. Returns the URI with its scheme.
+     */
+    pub fn from_uri(uri: &str) -> (Self, String) {
+        match uri.split_once("://") {
+            None => (Self::Remote, format!("ws://{}", uri)),
+            Some(("mem" | "memory", _)) => (Self::Memory, String::from("mem://")),
+            Some(("file" | "rocksdb", _)) => (Self::File, String::from(uri)),
+            Some(_) => (Self::Remote, String::from(uri)),
+        }
+    }
+}
+
+impl DatabaseState {
+    /**
+     * A method to get the state from its stored value.
+     */
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Connecting,
+            1 => Self::Connected,
+            _ => Self::Degraded,
+        }
+    }
+}
 
 // Functions
 /**
  * A method to initialize the database.
 */
-pub async fn initialize_db() -> Result<(), Box<dyn std::error::Error>> {
+pub async fn initialize_db(config: &DatabaseConfig) -> Result<(), Box<dyn std::error::Error>> {
     // Connect to the database.
     info!("Initializing the database.");
-    signin(
-        &var("DATABASE_URI")?,
-        &var("DATABASE_USER")?,
-        &var("DATABASE_PASS")?,
-    )
-    .await?;
-
-    // Define the namespace.
-    DATABASE.use_ns("watchlist").use_db("api").await?;
+    connect_db(config).await?;
 
     // Run the migrations
-    migrations().await?;
+    run_migrations().await?;
+
+    // Remove the rows left behind by the old deletes.
+    cleanup_orphans().await?;
 
     info!("Successfully initialized the database.");
     Ok(())
 }
 
+/**
+ * A method to connect to the database and define the namespace. No migration is run.
+ * The failed attempts are retried with backoff until the connect timeout is reached.
+ */
+pub async fn connect_db(config: &DatabaseConfig) -> Result<(), Box<dyn std::error::Error>> {
+    config.validate()?;
+    let deadline = Instant::now() + config.connect_timeout;
+    let mut attempt = 0;
+    loop {
+        let e = match try_connect_db(config).await {
+            Ok(()) => break,
+            Err(e) => e,
+        };
+
+        let wait = backoff(attempt, config.reconnect_max_backoff);
+        if Instant::now() + wait > deadline {
+            return Err(e.into());
+        }
+        warn!(
+            "Couldn\'t connect to the database. Retrying in {}s. {}",
+            wait.as_secs(),
+            e
+        );
+        tokio::time::sleep(wait).await;
+        attempt += 1;
+    }
+
+    set_database_state(DatabaseState::Connected);
+    Ok(())
+}
+
+/**
+ * A method to connect to the database once and define the namespace.
+ * The embedded engines don't need to signin.
+ */
+async fn try_connect_db(config: &DatabaseConfig) -> surrealdb::Result<()> {
+    let (engine, uri) = DatabaseEngine::from_uri(config.uri.as_deref().unwrap_or_default());
+    match engine {
+        DatabaseEngine::Remote => {
+            let user = config.user.as_deref().unwrap_or_default();
+            let pass = config.pass.as_deref().unwrap_or_default();
+            signin(&uri, user, pass).await?
+        }
+        DatabaseEngine::Memory | DatabaseEngine::File => {
+            info!("Starting the embedded {:?} database...", engine);
+            DATABASE.connect(uri.as_str()).await?;
+            if engine == DatabaseEngine::Memory {
+                warn!("The database is in memory. The data will be lost when the API stops.");
+            }
+        }
+    }
+
+    // Define the namespace.
+    DATABASE
+        .use_ns(&config.namespace)
+        .use_db(&config.name)
+        .await
+}
+
 /**
  * A method to connect and signin into the database.
+ * A previous attempt may have connected before failing to signin, so the connection is kept.
  */
 async fn signin(uri: &str, username: &str, password: &str) -> surrealdb::Result<()> {
     // Connect
     info!("Connecting to the database...");
-    DATABASE.connect::<Ws>(uri).await?;
+    match DATABASE.connect(uri).await {
+        Err(surrealdb::Error::Api(Api::AlreadyConnected)) => (),
+        res => res?,
+    }
 
     // Signin as root.
     info!("Signing in in the database...");
@@ -60,14 +247,128 @@ async fn signin(uri: &str, username: &str, password: &str) -> surrealdb::Result<
 }
 
 /**
- * A method to run the migrations.
+ * A method to start the task that supervises the database connection.
+ * The connection is checked on an interval. When it's lost, the state is degraded and
+ * the supervisor signs in again with backoff until the database answers.
+*/
+pub fn initialize_db_supervisor(config: DatabaseConfig) {
+    info!("Initializing the database supervisor...");
+    tokio::spawn(async move {
+        let mut attempt = 0;
+        loop {
+            let state = database_state();
+            let wait = match state {
+                DatabaseState::Degraded => backoff(attempt, config.reconnect_max_backoff),
+                _ => config.health_check_interval,
+            };
+            tokio::time::sleep(wait).await;
+
+            match check_db(&config, state == DatabaseState::Degraded).await {
+                Ok(()) => {
+                    if state == DatabaseState::Degraded {
+                        info!("The connection to the database was restored.");
+                    }
+                    set_database_state(DatabaseState::Connected);
+                    attempt = 0;
+                }
+                Err(e) if state == DatabaseState::Degraded => {
+                    warn!("Couldn\'t reconnect to the database. {}", e);
+                    attempt = attempt.saturating_add(1);
+                }
+                Err(e) => {
+                    error!("Lost the connection to the database. {}", e);
+                    set_database_state(DatabaseState::Degraded);
+                }
+            }
+        }
+    });
+}
+
+/**
+ * A method to check if the database answers. When reconnecting, the remote engine signs in again
+ * and the namespace is defined again, as the database may have restarted.
  */
-async fn migrations() -> surrealdb::Result<()> {
-    info!("Running the migrations.");
-    User::migration().await?;
-    // Media::migration().await?;
-    Watchlist::migration().await?;
-    info!("Successfully ran the migrations.");
+async fn check_db(config: &DatabaseConfig, reconnect: bool) -> Result<(), String> {
+    let check = async {
+        DATABASE.health().await?;
+        if !reconnect {
+            return Ok(());
+        }
 
-    Ok(())
+        let (engine, _) = DatabaseEngine::from_uri(config.uri.as_deref().unwrap_or_default());
+        if engine == DatabaseEngine::Remote {
+            let username = config.user.as_deref().unwrap_or_default();
+            let password = config.pass.as_deref().unwrap_or_default();
+            DATABASE.signin(Root { username, password }).await?;
+        }
+        DATABASE
+            .use_ns(&config.namespace)
+            .use_db(&config.name)
+            .await
+    };
+
+    match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check).await {
+        Err(_) => Err(String::from("The database didn\'t answer in time.")),
+        Ok(Err(e)) => Err(e.to_string()),
+        Ok(Ok(())) => Ok(()),
+    }
+}
+
+/**
+ * A method to get the delay before the next connection attempt.
+ * The delay starts at 1 second and doubles on each attempt, up to the max.
+ */
+pub fn backoff(attempt: u32, max: Duration) -> Duration {
+    Duration::from_secs(1u64.checked_shl(attempt).unwrap_or(u64::MAX)).min(max)
+}
+
+/**
+ * A method to get the state of the connection to the database.
+ */
+pub fn database_state() -> DatabaseState {
+    DatabaseState::from_u8(DATABASE_STATE.load(Ordering::Relaxed))
+}
+
+/**
+ * A method to define the state of the connection to the database.
+ */
+pub fn set_database_state(state: DatabaseState) {
+    DATABASE_STATE.store(state as u8, Ordering::Relaxed);
+}
+
+/**
+ * A method to remove the rows that point to deleted records.
+ * Watchlists without owner, members without user, medias without watchlist, episodes without media
+ * and invitations without watchlist, inviter or invitee are removed.
+ */
+pub async fn cleanup_orphans() -> surrealdb::Result<OrphanReport> {
+    info!("Cleaning up the orphans...");
+    let report: Option<OrphanReport> = DATABASE
+        .query(
+            "
+                BEGIN TRANSACTION;
+                LET $watchlists = (DELETE watchlist WHERE owner.id IS NONE RETURN BEFORE);
+                LET $members = (UPDATE watchlist SET members = members[WHERE user.id IS NOT NONE] WHERE members[WHERE user.id IS NONE] RETURN BEFORE);
+                LET $medias = (DELETE media WHERE watchlist.id IS NONE RETURN BEFORE);
+                LET $episodes = (DELETE episode WHERE media.id IS NONE RETURN BEFORE);
+                LET $invitations = (DELETE invitation WHERE watchlist.id IS NONE OR inviter.id IS NONE OR invitee.id IS NONE RETURN BEFORE);
+                RETURN {
+                    watchlists: array::len($watchlists),
+                    members: array::len($members),
+                    medias: array::len($medias),
+                    episodes: array::len($episodes),
+                    invitations: array::len($invitations),
+                };
+                COMMIT TRANSACTION;
+            ",
+        )
+        .await?
+        .take(0)?;
+
+    let report = report.unwrap_or_default();
+    info!(
+        "Removed {} watchlists, {} medias, {} episodes and {} invitations. Cleaned the members of {} watchlists.",
+        report.watchlists, report.medias, report.episodes, report.invitations, report.members
+    );
+    Ok(report)
 }
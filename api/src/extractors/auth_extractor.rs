@@ -0,0 +1,105 @@
+// Libs
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use axum_auth::{AuthBasic, AuthBearer};
+use surrealdb::sql::{Id, Thing};
+use tracing::{error, info, warn};
+
+use crate::controllers::api_error::ApiError;
+use crate::logger::record_user_id;
+use crate::models::user_model::User;
+use crate::security::{validate_token, TokenKind};
+
+// Structs
+/**
+ * An extractor to get the logged user from a Bearer access token.
+ * The token must have the current token version of the user, so the tokens of deleted users or older passwords are rejected.
+*/
+pub struct AuthUser(pub Thing);
+
+/**
+ * An extractor to get the username and the password from a Basic authorization.
+ * A missing or malformed header is rejected as invalid credentials.
+*/
+pub struct AuthCredentials(pub (String, Option<String>));
+
+/**
+ * An extractor to get the raw Bearer token, without checking it.
+ * A missing or malformed header is rejected as an invalid token.
+*/
+pub struct AuthToken(pub String);
+
+// Implementations
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Get the token from the Authorization header.
+        let AuthBearer(token) = match AuthBearer::from_request_parts(parts, state).await {
+            Err(_) => {
+                info!("Bearer token not included.");
+                return Err(ApiError::InvalidToken);
+            }
+            Ok(token) => token,
+        };
+
+        // Check the token.
+        let claims = match validate_token(&token, TokenKind::Access) {
+            None => {
+                warn!("The access token was rejected.");
+                return Err(ApiError::InvalidToken);
+            }
+            Some(claims) => claims,
+        };
+
+        // Check if the token wasn't revoked.
+        let user_id = Thing {
+            id: Id::from(claims.sub),
+            tb: String::from("user"),
+        };
+        match User::get_token_version(&user_id).await {
+            Err(e) => {
+                error!("Couldn't get the token version. {}", e);
+                Err(ApiError::Internal("check the token"))
+            }
+            Ok(version) if version != Some(claims.ver) => {
+                warn!("The access token was revoked.");
+                Err(ApiError::InvalidToken)
+            }
+            Ok(_) => {
+                record_user_id(&user_id);
+                Ok(Self(user_id))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthCredentials {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match AuthBasic::from_request_parts(parts, state).await {
+            Err(_) => {
+                info!("Basic authorization not included.");
+                Err(ApiError::InvalidCredentials)
+            }
+            Ok(AuthBasic(user_auth)) => Ok(Self(user_auth)),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthToken {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match AuthBearer::from_request_parts(parts, state).await {
+            Err(_) => {
+                info!("Bearer token not included.");
+                Err(ApiError::InvalidToken)
+            }
+            Ok(AuthBearer(token)) => Ok(Self(token)),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod auth_extractor;
+pub mod validated_json_extractor;
+pub mod validated_query_extractor;
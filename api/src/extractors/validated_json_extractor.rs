@@ -0,0 +1,49 @@
+// Libs
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use tracing::{info, warn};
+
+use crate::controllers::api_error::ApiError;
+use crate::validation::Validate;
+
+// Structs
+/**
+ * An extractor to get a JSON body that was normalized and validated by its rules.
+ * All the invalid fields are rejected at once, before any database call.
+*/
+pub struct ValidatedJson<T>(pub T);
+
+// Implementations
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        // Parse the body.
+        let Json(mut body) = match Json::<T>::from_request(req, state).await {
+            Err(e) => {
+                info!("The body was rejected. {}", e.body_text());
+                return Err(ApiError::InvalidBody(e.body_text()));
+            }
+            Ok(body) => body,
+        };
+
+        // Normalize and check the fields.
+        body.normalize();
+        let fields = body.validate();
+        if !fields.is_empty() {
+            warn!("The body has {} invalid fields.", fields.len());
+            return Err(ApiError::InvalidFields(fields));
+        }
+
+        Ok(Self(body))
+    }
+}
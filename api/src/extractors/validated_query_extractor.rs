@@ -0,0 +1,49 @@
+// Libs
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use serde::de::DeserializeOwned;
+use tracing::{info, warn};
+
+use crate::controllers::api_error::ApiError;
+use crate::validation::Validate;
+
+// Structs
+/**
+ * An extractor to get a query string that was normalized and validated by its rules.
+ * Like the ValidatedJson, the rejections are answered with the typed errors of the API.
+*/
+pub struct ValidatedQuery<T>(pub T);
+
+// Implementations
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ValidatedQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Parse the query.
+        let Query(mut query) = match Query::<T>::from_request_parts(parts, state).await {
+            Err(e) => {
+                info!("The query was rejected. {}", e.body_text());
+                return Err(ApiError::InvalidBody(e.body_text()));
+            }
+            Ok(query) => query,
+        };
+
+        // Normalize and check the fields.
+        query.normalize();
+        let fields = query.validate();
+        if !fields.is_empty() {
+            warn!("The query has {} invalid fields.", fields.len());
+            return Err(ApiError::InvalidFields(fields));
+        }
+
+        Ok(Self(query))
+    }
+}
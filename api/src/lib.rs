@@ -0,0 +1,18 @@
+// Libs
+pub mod config;
+pub mod controllers;
+pub mod database;
+pub mod extractors;
+pub mod limiter;
+pub mod logger;
+pub mod metrics;
+pub mod middlewares;
+pub mod migrations;
+pub mod models;
+pub mod openapi;
+pub mod router;
+pub mod security;
+pub mod shutdown;
+pub mod telemetry;
+pub mod trash;
+pub mod validation;
@@ -0,0 +1,289 @@
+// Libs
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::duration_secs;
+
+// Data
+static LIMITER: OnceCell<Limiter> = OnceCell::new();
+/**
+ * The keys tracked by each map. Past it, the least recently seen quarter is forgotten.
+*/
+pub static MAX_TRACKED_KEYS: usize = 10_000;
+
+// Structs
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimiterConfig {
+    pub enabled: bool,
+    pub ip_burst: u32,
+    pub ip_per_minute: u32,
+    pub account_burst: u32,
+    pub account_per_minute: u32,
+    pub login_max_failures: u32,
+    #[serde(rename = "login_lockout_base_secs", with = "duration_secs")]
+    pub login_lockout_base: Duration,
+    #[serde(rename = "login_lockout_max_secs", with = "duration_secs")]
+    pub login_lockout_max: Duration,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+#[derive(Debug)]
+struct LoginFailures {
+    count: u32,
+    locked_until: Option<Instant>,
+    last_seen: Instant,
+}
+
+pub struct Limiter {
+    pub config: LimiterConfig,
+    ip_buckets: Mutex<HashMap<String, TokenBucket>>,
+    account_buckets: Mutex<HashMap<String, TokenBucket>>,
+    login_failures: Mutex<HashMap<String, LoginFailures>>,
+}
+
+// Implementations
+impl Default for LimiterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ip_burst: 60,
+            ip_per_minute: 60,
+            account_burst: 30,
+            account_per_minute: 30,
+            login_max_failures: 5,
+            login_lockout_base: Duration::from_secs(30),
+            login_lockout_max: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl LimiterConfig {
+    /**
+     * A method to check if the configuration values are coherent.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ip_burst == 0 || self.account_burst == 0 {
+            return Err(String::from(
+                "The rate limit bursts must be greater than 0.",
+            ));
+        }
+        if self.ip_per_minute == 0 || self.account_per_minute == 0 {
+            return Err(String::from(
+                "The rate limit refill rates must be greater than 0.",
+            ));
+        }
+        if self.login_max_failures == 0 {
+            return Err(String::from(
+                "The rate_limit.login_max_failures must be greater than 0.",
+            ));
+        }
+        if self.login_lockout_base > self.login_lockout_max {
+            return Err(String::from(
+                "The login lockout base can't be greater than the max lockout.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, now: Instant) -> Self {
+        Self {
+            tokens: capacity as f64,
+            last_refill: now,
+        }
+    }
+
+    /**
+     * A method to take a token from the bucket. Returns how long to wait if the bucket is empty.
+     */
+    fn try_take(&mut self, capacity: u32, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        // Refill the bucket.
+        let refill_per_sec = per_minute as f64 / 60.0;
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * refill_per_sec).min(capacity as f64);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let missing = 1.0 - self.tokens;
+        Err(Duration::from_secs_f64(missing / refill_per_sec))
+    }
+
+    /**
+     * A method to check if the bucket would be full at the given instant.
+     */
+    fn is_full(&self, capacity: u32, per_minute: u32, now: Instant) -> bool {
+        let refill_per_sec = per_minute as f64 / 60.0;
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens + elapsed * refill_per_sec >= capacity as f64
+    }
+}
+
+impl Limiter {
+    pub fn new(config: LimiterConfig) -> Self {
+        Self {
+            config,
+            ip_buckets: Mutex::new(HashMap::new()),
+            account_buckets: Mutex::new(HashMap::new()),
+            login_failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /**
+     * A method to take a token from the bucket of the given ip.
+     */
+    pub fn check_ip(&self, ip: &str) -> Result<(), Duration> {
+        let (burst, per_minute) = (self.config.ip_burst, self.config.ip_per_minute);
+        Self::take(&self.ip_buckets, ip, burst, per_minute)
+    }
+
+    /**
+     * A method to take a token from the bucket of the given account.
+     */
+    pub fn check_account(&self, account: &str) -> Result<(), Duration> {
+        let (burst, per_minute) = (self.config.account_burst, self.config.account_per_minute);
+        Self::take(&self.account_buckets, account, burst, per_minute)
+    }
+
+    /**
+     * A method to check if the login of the given username is locked. Returns the remaining lockout.
+     */
+    pub fn check_login(&self, username: &str) -> Result<(), Duration> {
+        let failures = self.login_failures.lock().expect("Poisoned lock.");
+        let now = Instant::now();
+        match failures.get(username).and_then(|f| f.locked_until) {
+            Some(locked_until) if locked_until > now => Err(locked_until - now),
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * A method to register a failed login. Locks the username after too many failures.
+     * Each new failure after the limit doubles the lockout, up to the max lockout.
+     */
+    pub fn register_login_failure(&self, username: &str) {
+        let mut failures = self.login_failures.lock().expect("Poisoned lock.");
+        let now = Instant::now();
+        // The locked usernames are seen until their lockout ends, so they're forgotten last.
+        if failures.len() >= MAX_TRACKED_KEYS && !failures.contains_key(username) {
+            evict_oldest(&mut failures, |f| {
+                f.locked_until
+                    .map_or(f.last_seen, |until| until.max(f.last_seen))
+            });
+        }
+
+        let entry = failures
+            .entry(String::from(username))
+            .or_insert(LoginFailures {
+                count: 0,
+                locked_until: None,
+                last_seen: now,
+            });
+        entry.count += 1;
+        entry.last_seen = now;
+
+        if entry.count >= self.config.login_max_failures {
+            let exponent = (entry.count - self.config.login_max_failures).min(31);
+            let lockout = self
+                .config
+                .login_lockout_base
+                .saturating_mul(2u32.saturating_pow(exponent))
+                .min(self.config.login_lockout_max);
+            warn!(
+                "Too many failed logins for {}. Locking it for {}s.",
+                username,
+                lockout.as_secs()
+            );
+            entry.locked_until = Some(now + lockout);
+        }
+    }
+
+    /**
+     * A method to clear the failed logins of the given username.
+     */
+    pub fn register_login_success(&self, username: &str) {
+        let mut failures = self.login_failures.lock().expect("Poisoned lock.");
+        failures.remove(username);
+    }
+
+    fn take(
+        buckets: &Mutex<HashMap<String, TokenBucket>>,
+        key: &str,
+        capacity: u32,
+        per_minute: u32,
+    ) -> Result<(), Duration> {
+        let mut buckets = buckets.lock().expect("Poisoned lock.");
+        let now = Instant::now();
+
+        // Forget the idle buckets, they are the same as a new one. Then the least recently used ones.
+        if buckets.len() >= MAX_TRACKED_KEYS && !buckets.contains_key(key) {
+            buckets.retain(|_, bucket| !bucket.is_full(capacity, per_minute, now));
+            if buckets.len() >= MAX_TRACKED_KEYS {
+                evict_oldest(&mut buckets, |bucket| bucket.last_refill);
+            }
+        }
+
+        buckets
+            .entry(String::from(key))
+            .or_insert_with(|| TokenBucket::new(capacity, now))
+            .try_take(capacity, per_minute, now)
+    }
+}
+
+// Functions
+/**
+ * A method to forget the least recently seen quarter of the tracked keys.
+*/
+fn evict_oldest<T>(entries: &mut HashMap<String, T>, last_seen: impl Fn(&T) -> Instant) {
+    let mut keys: Vec<(Instant, String)> = entries
+        .iter()
+        .map(|(key, entry)| (last_seen(entry), key.clone()))
+        .collect();
+    keys.sort_unstable();
+    info!(
+        "Forgetting the {} oldest keys of the limiter...",
+        MAX_TRACKED_KEYS / 4
+    );
+    for (_, key) in keys.into_iter().take(MAX_TRACKED_KEYS / 4) {
+        entries.remove(&key);
+    }
+}
+
+/**
+ * A method to initialize the limiter using the given configuration.
+*/
+pub fn initialize_limiter(config: LimiterConfig) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Initializing the rate limiter...");
+    config.validate()?;
+    if LIMITER.set(Limiter::new(config)).is_err() {
+        warn!("The rate limiter was already initialized.");
+    }
+
+    info!("Successfully initialized the rate limiter.");
+    Ok(())
+}
+
+/**
+ * A method to get the limiter. If it wasn't initialized, the default configuration is used.
+*/
+pub fn limiter() -> &'static Limiter {
+    LIMITER.get_or_init(|| Limiter::new(LimiterConfig::default()))
+}
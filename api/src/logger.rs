@@ -0,0 +1,106 @@
+// Libs
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use tracing::{info, level_filters::LevelFilter, Span};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, Layer};
+
+use crate::telemetry::{telemetry_layer, TelemetryConfig};
+
+// Enums
+/**
+ * The format of the log lines.
+ * text: human readable lines. The fields of the spans prefix the message.
+ * json: one JSON object per line, with the fields of the current span.
+*/
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+// Structs
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    pub level: String,
+}
+
+// Implementations
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("The log format {} is unknown.", value)),
+        }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            level: String::from("info"),
+        }
+    }
+}
+
+impl LogConfig {
+    /**
+     * A method to check if the configuration values are coherent.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        self.level_filter().map(|_| ())
+    }
+
+    /**
+     * A method to get the max level of the log lines.
+     */
+    fn level_filter(&self) -> Result<LevelFilter, String> {
+        LevelFilter::from_str(&self.level).map_err(|_| {
+            String::from("The log.level must be one of off, error, warn, info, debug or trace.")
+        })
+    }
+}
+
+// Functions
+/**
+ * A method to record the authenticated user in the span of the current request.
+*/
+pub fn record_user_id(user_id: &Thing) {
+    Span::current().record("user_id", user_id.to_string());
+}
+
+/**
+ * A method to initialize the logger and the export of the spans, if enabled.
+ * Does nothing if some logger was already initialized.
+*/
+pub fn initialize_logger(config: &LogConfig, telemetry: &TelemetryConfig) -> Result<(), String> {
+    let level = config.level_filter()?;
+    let fmt_layer = match config.format {
+        LogFormat::Text => fmt::layer().boxed(),
+        LogFormat::Json => fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    };
+
+    let _ = tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(telemetry_layer(telemetry)?)
+        .with(level)
+        .try_init();
+    if let Some(endpoint) = &telemetry.otlp_endpoint {
+        info!("The spans are exported to {}.", endpoint);
+    }
+
+    Ok(())
+}
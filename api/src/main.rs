@@ -1,42 +1,98 @@
 // Libs
 use std::{net::SocketAddr, process::exit};
 
+use clap::Parser;
 use tracing::{error, info};
 
-use database::initialize_db;
-mod controllers;
-mod database;
-mod middlewares;
-mod models;
-mod router;
-mod security;
-
-// Data
-static PORT: i16 = 3000;
+use api::config::{initialize_config, Cli, Command, Config};
+use api::database::{connect_db, initialize_db, initialize_db_supervisor};
+use api::limiter::initialize_limiter;
+use api::logger::{initialize_logger, LogConfig};
+use api::migrations::{run_migration_mode, MigrationMode};
+use api::router;
+use api::security::initialize_security;
+use api::shutdown::{serve_with_shutdown, shutdown_signal};
+use api::telemetry::{shutdown_telemetry, TelemetryConfig};
+use api::trash::initialize_trash_purge;
 
 // Main function
 #[tokio::main]
 async fn main() {
+    // Load the configuration.
+    let cli = Cli::parse();
+    let config = match Config::load(&cli) {
+        Err(e) => {
+            let _ = initialize_logger(&LogConfig::default(), &TelemetryConfig::default());
+            error!("Couldn\'t load the configuration. {}", e);
+            exit(1);
+        }
+        Ok(config) => config,
+    };
+    if cli.print_config {
+        print!("{}", config.to_redacted_toml());
+        return;
+    }
+
     // Initialize the logger.
-    tracing_subscriber::fmt::init();
+    if let Err(e) = initialize_logger(&config.log, &config.telemetry) {
+        eprintln!("Couldn\'t initialize the logger. {}", e);
+        exit(1);
+    }
+    initialize_config(config.clone());
+
+    // Check if only the migrations should run.
+    let mode = match cli.command {
+        None => MigrationMode::Apply,
+        Some(Command::Migrations { mode }) => mode,
+    };
+    if mode != MigrationMode::Apply {
+        if let Err(e) = connect_db(&config.database).await {
+            error!("Couldn\'t connect to the database. {}", e);
+            exit(1);
+        }
+        if let Err(e) = run_migration_mode(mode).await {
+            error!("Couldn\'t check the migrations. {}", e);
+            exit(1);
+        }
+        return;
+    }
+
+    if let Err(e) = initialize_security(&config.security) {
+        error!("Couldn\'t initialize the security. {}", e);
+        exit(1);
+    }
+
+    if let Err(e) = initialize_limiter(config.rate_limit) {
+        error!("Couldn\'t initialize the rate limiter. {}", e);
+        exit(1);
+    }
 
-    if let Err(e) = initialize_db().await {
+    if let Err(e) = initialize_db(&config.database).await {
         error!("Couldn\'t initialize the database. {}", e);
         exit(1);
     }
+    initialize_db_supervisor(config.database.clone());
+
+    if let Err(e) = initialize_trash_purge(config.trash) {
+        error!("Couldn\'t initialize the trash purge. {}", e);
+        exit(1);
+    }
 
     // Open the server.
-    info!("Starting server on port {}...", PORT);
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", PORT)).await;
+    let address = SocketAddr::new(config.server.host, config.server.port);
+    info!("Starting server on {}...", address);
+    let listener = tokio::net::TcpListener::bind(address).await;
     if let Err(e) = listener {
-        error!("Couldn\'t listen on port {}. {}", PORT, e);
+        error!("Couldn\'t listen on {}. {}", address, e);
         exit(1);
     }
 
     info!("Server started successfully.");
     let listener = listener.unwrap();
-    let router = router::get_router().into_make_service_with_connect_info::<SocketAddr>();
-    axum::serve(listener, router)
+    let deadline = config.server.shutdown_timeout;
+    serve_with_shutdown(listener, router::get_router(), shutdown_signal(), deadline)
         .await
         .expect("Couldn\'t serve the port.");
+    info!("Server stopped.");
+    shutdown_telemetry();
 }
@@ -0,0 +1,198 @@
+// Libs
+use std::{future::IntoFuture, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use serde::Deserialize;
+use tracing::{info_span, warn, Instrument};
+
+use crate::database::DATABASE;
+
+// Data
+/**
+ * The registry with all the metrics exposed in GET /metrics.
+*/
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+pub static HTTP_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("http_requests_total", "The number of handled requests."),
+        &["method", "route", "status"],
+    ))
+});
+
+pub static HTTP_REQUEST_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register(HistogramVec::new(
+        HistogramOpts::new(
+            "http_request_duration_seconds",
+            "The time to handle the requests.",
+        ),
+        &["method", "route", "status"],
+    ))
+});
+
+pub static DATABASE_QUERY_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register(HistogramVec::new(
+        HistogramOpts::new(
+            "database_query_duration_seconds",
+            "The time the database took to answer the queries of the models.",
+        )
+        .buckets(vec![
+            0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+        ]),
+        &["model", "operation", "result"],
+    ))
+});
+
+pub static LOGIN_FAILURES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("login_failures_total", "The number of failed logins."),
+        &["reason"],
+    ))
+});
+
+pub static RECORDS_TOTAL: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register(IntGaugeVec::new(
+        Opts::new(
+            "records_total",
+            "The number of users, watchlists and medias. The trashed ones aren't counted.",
+        ),
+        &["table"],
+    ))
+});
+
+// Structs
+#[derive(Debug, Default, Deserialize)]
+struct RecordCount {
+    users: i64,
+    watchlists: i64,
+    medias: i64,
+}
+
+// Functions
+/**
+ * A method to register some metric in the registry.
+*/
+fn register<T: prometheus::core::Collector + Clone + 'static>(metric: prometheus::Result<T>) -> T {
+    let metric = metric.expect("Couldn\'t create the metric.");
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("Couldn\'t register the metric.");
+    metric
+}
+
+/**
+ * A method to count the requests and measure their duration, by route and status.
+ * The route is the matched path, so the ids don't create new series.
+*/
+pub async fn track_metrics(req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = req.method().to_string();
+    let route = match req.extensions().get::<MatchedPath>() {
+        None => String::from("unmatched"),
+        Some(path) => path.as_str().to_string(),
+    };
+
+    let res = next.run(req).await;
+
+    let status = res.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    HTTP_REQUESTS_TOTAL.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION_SECONDS
+        .with_label_values(&labels)
+        .observe(start.elapsed().as_secs_f64());
+    res
+}
+
+/**
+ * A method to run some query of the models in its own span, measuring how long the database took to answer.
+*/
+pub async fn observe_query<Q, T>(
+    model: &'static str,
+    operation: &'static str,
+    query: Q,
+) -> surrealdb::Result<T>
+where
+    Q: IntoFuture<Output = surrealdb::Result<T>>,
+{
+    let span = info_span!(
+        "query",
+        model,
+        operation,
+        db.system = "surrealdb",
+        otel.name = %format!("{}.{}", model, operation),
+        otel.kind = "client",
+    );
+    let start = Instant::now();
+    let res = query.into_future().instrument(span).await;
+    let result = match res {
+        Err(_) => "error",
+        Ok(_) => "ok",
+    };
+    DATABASE_QUERY_DURATION_SECONDS
+        .with_label_values(&[model, operation, result])
+        .observe(start.elapsed().as_secs_f64());
+    res
+}
+
+/**
+ * A method to count some failed login.
+ * reason: missing_password, unknown_user, wrong_password or locked.
+*/
+pub fn count_login_failure(reason: &str) {
+    LOGIN_FAILURES_TOTAL.with_label_values(&[reason]).inc();
+}
+
+/**
+ * A method to update the gauges with the number of records in the database.
+*/
+async fn update_record_gauges() -> surrealdb::Result<()> {
+    let count: Option<RecordCount> = observe_query(
+        "metrics",
+        "update_record_gauges",
+        DATABASE.query(
+            "
+                RETURN {
+                    users: array::len(SELECT id FROM user),
+                    watchlists: array::len(SELECT id FROM watchlist WHERE deleted_at IS NONE),
+                    medias: array::len(SELECT id FROM media WHERE deleted_at IS NONE),
+                };
+            ",
+        ),
+    )
+    .await?
+    .take(0)?;
+
+    let count = count.unwrap_or_default();
+    RECORDS_TOTAL.with_label_values(&["user"]).set(count.users);
+    RECORDS_TOTAL
+        .with_label_values(&["watchlist"])
+        .set(count.watchlists);
+    RECORDS_TOTAL
+        .with_label_values(&["media"])
+        .set(count.medias);
+    Ok(())
+}
+
+/**
+ * A method to get all the metrics in the Prometheus text format.
+ * The record gauges are updated first. If the database doesn't answer, the last values are kept.
+*/
+pub async fn gather() -> String {
+    if let Err(e) = update_record_gauges().await {
+        warn!("Couldn\'t count the records. {}", e);
+    }
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .expect("Couldn\'t encode the metrics.");
+    String::from_utf8(buffer).expect("The metrics aren\'t valid UTF-8.")
+}
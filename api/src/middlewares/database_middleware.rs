@@ -0,0 +1,29 @@
+// Libs
+use axum::{
+    extract::Request,
+    http::header::RETRY_AFTER,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::config::config;
+use crate::controllers::api_error::ApiError;
+use crate::database::{database_state, DatabaseState};
+
+// Functions
+/**
+ * A method to answer 503 while the connection to the database is degraded,
+ * instead of letting the request wait for the database until the timeout.
+*/
+pub async fn database_available(req: Request, next: Next) -> Response {
+    if database_state() != DatabaseState::Degraded {
+        return next.run(req).await;
+    }
+
+    let retry_after = config().database.health_check_interval.as_secs().max(1);
+    (
+        [(RETRY_AFTER, retry_after.to_string())],
+        ApiError::DatabaseUnavailable,
+    )
+        .into_response()
+}
@@ -1,6 +1,6 @@
+// Libs
 use std::net::SocketAddr;
 
-// Libs
 use axum::{
     extract::{ConnectInfo, Request},
     middleware::Next,
@@ -11,21 +11,17 @@ use tracing::info;
 
 // Functions
 pub async fn log_stream(mut req: Request, next: Next) -> Response {
-    let ip = req
-        .extract_parts::<ConnectInfo<SocketAddr>>()
-        .await
-        .expect("Couldn\'t get the connection information.");
-    info!(
-        "Request to from [{}] to {} {}",
-        ip.to_string(),
-        req.method(),
-        req.uri()
-    );
+    // The connection information is missing if the server wasn't made with it.
+    let ip = match req.extract_parts::<Option<ConnectInfo<SocketAddr>>>().await {
+        Ok(Some(ConnectInfo(ip))) => ip.to_string(),
+        _ => String::from("unknown"),
+    };
+    info!("Request to from [{}] to {} {}", ip, req.method(), req.uri());
     let res = next.run(req).await;
     info!(
         "Returning response #{} to the client [{}].",
         res.status(),
-        ip.to_string()
+        ip
     );
     res
 }
@@ -1,2 +1,5 @@
 pub mod acceptable_middleware;
+pub mod database_middleware;
 pub mod log_middleware;
+pub mod rate_limit_middleware;
+pub mod request_id_middleware;
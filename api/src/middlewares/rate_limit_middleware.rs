@@ -0,0 +1,70 @@
+// Libs
+use std::{net::SocketAddr, time::Duration};
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::header::RETRY_AFTER,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    RequestExt,
+};
+use axum_auth::{AuthBasic, AuthBearer};
+use tracing::warn;
+
+use crate::controllers::api_error::ApiError;
+use crate::limiter::limiter;
+use crate::metrics::count_login_failure;
+use crate::security::{validate_token, TokenKind};
+use crate::validation::username_key;
+
+// Functions
+pub async fn rate_limit(mut req: Request, next: Next) -> Response {
+    let limiter = limiter();
+    if !limiter.config.enabled {
+        return next.run(req).await;
+    }
+
+    // Check the ip bucket. Without the connection information, only the accounts are limited.
+    match req.extract_parts::<Option<ConnectInfo<SocketAddr>>>().await {
+        Ok(Some(ConnectInfo(ip))) => {
+            if let Err(retry_after) = limiter.check_ip(&ip.ip().to_string()) {
+                warn!("Too many requests from [{}].", ip);
+                return too_many_requests(retry_after, ApiError::RateLimited);
+            }
+        }
+        _ => warn!("Couldn\'t get the connection information. The ip isn\'t limited."),
+    }
+
+    // Check the account bucket and the login lockout.
+    // The keys are prefixed by their kind, so some username never shares the bucket of some user id.
+    if let Ok(AuthBasic((username, _))) = req.extract_parts::<AuthBasic>().await {
+        let username = username_key(&username);
+        if let Err(retry_after) = limiter.check_login(&username) {
+            warn!("The login of {} is locked.", username);
+            count_login_failure("locked");
+            return too_many_requests(retry_after, ApiError::LoginLocked);
+        }
+        if let Err(retry_after) = limiter.check_account(&format!("username:{}", username)) {
+            warn!("Too many requests from {}.", username);
+            return too_many_requests(retry_after, ApiError::RateLimited);
+        }
+    } else if let Ok(AuthBearer(token)) = req.extract_parts::<AuthBearer>().await {
+        if let Some(claims) = validate_token(&token, TokenKind::Access) {
+            if let Err(retry_after) = limiter.check_account(&format!("user:{}", claims.sub)) {
+                warn!("Too many requests from user:{}.", claims.sub);
+                return too_many_requests(retry_after, ApiError::RateLimited);
+            }
+        }
+    }
+
+    next.run(req).await
+}
+
+/**
+ * A method to create a 429 response with the Retry-After header.
+*/
+fn too_many_requests(retry_after: Duration, error: ApiError) -> Response {
+    // Round up, so the client never retries too soon.
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    ([(RETRY_AFTER, secs.max(1).to_string())], error).into_response()
+}
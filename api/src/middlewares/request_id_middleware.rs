@@ -0,0 +1,62 @@
+// Libs
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use surrealdb::sql::Id;
+use tracing::{field::Empty, info_span, Instrument};
+
+use crate::telemetry::set_parent_from_headers;
+
+// Data
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+static REQUEST_ID_MAX_LENGTH: usize = 128;
+
+// Functions
+/**
+ * A method to run the request inside a span with its route, request id and user id.
+ * The X-Request-Id of the client is kept if it's valid. Otherwise a new one is generated.
+ * The user id is recorded once the user is authenticated.
+*/
+pub async fn request_span(mut req: Request, next: Next) -> Response {
+    let request_id = match req.headers().get(&REQUEST_ID_HEADER) {
+        Some(value) if is_valid_request_id(value) => value.clone(),
+        _ => HeaderValue::from_str(&Id::ulid().to_raw()).expect("The ulid isn\'t a valid header."),
+    };
+    req.headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), request_id.clone());
+
+    let route = match req.extensions().get::<MatchedPath>() {
+        None => String::from("unmatched"),
+        Some(path) => path.as_str().to_string(),
+    };
+    let span = info_span!(
+        "request",
+        method = %req.method(),
+        route = route.as_str(),
+        request_id = request_id.to_str().unwrap_or_default(),
+        user_id = Empty,
+        status = Empty,
+        otel.name = %format!("{} {}", req.method(), route),
+        otel.kind = "server",
+    );
+    set_parent_from_headers(&span, req.headers());
+
+    let mut res = next.run(req).instrument(span.clone()).await;
+    span.record("status", res.status().as_u16());
+    res.headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), request_id);
+    res
+}
+
+/**
+ * A method to check if the request id of the client can be used. It must be printable ASCII.
+*/
+fn is_valid_request_id(value: &HeaderValue) -> bool {
+    let bytes = value.as_bytes();
+    !bytes.is_empty()
+        && bytes.len() <= REQUEST_ID_MAX_LENGTH
+        && bytes.iter().all(|b| b.is_ascii_graphic())
+}
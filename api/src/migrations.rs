@@ -0,0 +1,288 @@
+// Libs
+use clap::ValueEnum;
+use ring::digest::{digest, SHA256};
+use serde::Deserialize;
+use surrealdb::{error::Db, sql::Datetime};
+use tracing::{info, warn};
+
+use crate::database::DATABASE;
+
+// Data
+/**
+ * The migrations, in the order they are applied. A migration must never change after being released.
+ * To evolve the schema, add a new file to the migrations folder and a new entry here.
+ * The rules of ./src/validation.rs are written as they were when the migration was added, so they're in the checksum.
+*/
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "user",
+        query: include_str!("../migrations/0001_user.surql"),
+    },
+    Migration {
+        version: 2,
+        name: "watchlist",
+        query: include_str!("../migrations/0002_watchlist.surql"),
+    },
+    Migration {
+        version: 3,
+        name: "invitation",
+        query: include_str!("../migrations/0003_invitation.surql"),
+    },
+    Migration {
+        version: 4,
+        name: "media",
+        query: include_str!("../migrations/0004_media.surql"),
+    },
+    Migration {
+        version: 5,
+        name: "unicode_fields",
+        query: include_str!("../migrations/0005_unicode_fields.surql"),
+    },
+    Migration {
+        version: 6,
+        name: "media_details",
+        query: include_str!("../migrations/0006_media_details.surql"),
+    },
+    Migration {
+        version: 7,
+        name: "episode",
+        query: include_str!("../migrations/0007_episode.surql"),
+    },
+    Migration {
+        version: 8,
+        name: "token_version",
+        query: include_str!("../migrations/0008_token_version.surql"),
+    },
+];
+
+// Enums
+/**
+ * apply: apply the pending migrations and start the server. The default.
+ * status: list the applied and pending migrations.
+ * dry-run: validate and print the pending migrations, without applying them.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum MigrationMode {
+    Apply,
+    Status,
+    DryRun,
+}
+
+// Structs
+#[derive(Debug)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub query: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppliedMigration {
+    version: u32,
+    checksum: String,
+    applied_at: Datetime,
+}
+
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub name: &'static str,
+    pub applied_at: Option<Datetime>,
+}
+
+// Implementations
+impl Migration {
+    /**
+     * A method to get the checksum of the migration. Used to detect changed migrations.
+     */
+    pub fn checksum(&self) -> String {
+        hex::encode(digest(&SHA256, self.query.as_bytes()))
+    }
+}
+
+// Functions
+/**
+ * A method to apply all the pending migrations.
+ * Each migration runs in its own transaction with the record that marks it as applied.
+*/
+pub async fn run_migrations() -> Result<(), Box<dyn std::error::Error>> {
+    info!("Running the migrations...");
+    for migration in get_pending().await? {
+        info!(
+            "Applying the migration {:04}_{}...",
+            migration.version, migration.name
+        );
+        let mut response = DATABASE
+            .query("BEGIN TRANSACTION")
+            .query(migration.query)
+            .query("CREATE type::thing('_migrations', $version) CONTENT { version: $version, name: $name, checksum: $checksum, applied_at: time::now() }")
+            .query("COMMIT TRANSACTION")
+            .bind(("version", migration.version))
+            .bind(("name", migration.name))
+            .bind(("checksum", migration.checksum()))
+            .await?;
+
+        // The other statements of the failed transaction only report it was cancelled.
+        let mut errors: Vec<_> = response.take_errors().into_iter().collect();
+        errors.sort_by_key(|(i, _)| *i);
+        let cause = errors
+            .iter()
+            .find(|(_, e)| !matches!(e, surrealdb::Error::Db(Db::QueryNotExecuted)))
+            .or(errors.first());
+        if let Some((_, e)) = cause {
+            return Err(format!(
+                "Couldn\'t apply the migration {:04}_{}. {}",
+                migration.version, migration.name, e
+            )
+            .into());
+        }
+    }
+
+    info!("Successfully ran the migrations.");
+    Ok(())
+}
+
+/**
+ * A method to get the status of every known migration.
+*/
+pub async fn get_status() -> Result<Vec<MigrationStatus>, Box<dyn std::error::Error>> {
+    let applied = get_applied().await?;
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name,
+            applied_at: applied
+                .iter()
+                .find(|a| a.version == migration.version)
+                .map(|a| a.applied_at.clone()),
+        })
+        .collect())
+}
+
+/**
+ * A method to check the pending migrations without applying them. Returns the migrations that would run.
+*/
+pub async fn dry_run() -> Result<Vec<&'static Migration>, Box<dyn std::error::Error>> {
+    let pending = get_pending().await?;
+    for migration in &pending {
+        surrealdb::sql::parse(migration.query).map_err(|e| {
+            format!(
+                "The migration {:04}_{} is invalid. {}",
+                migration.version, migration.name, e
+            )
+        })?;
+    }
+
+    Ok(pending)
+}
+
+/**
+ * A method to run the given mode. The status and the dry-run only print the migrations.
+*/
+pub async fn run_migration_mode(mode: MigrationMode) -> Result<(), Box<dyn std::error::Error>> {
+    match mode {
+        MigrationMode::Apply => run_migrations().await?,
+        MigrationMode::Status => {
+            for status in get_status().await? {
+                match status.applied_at {
+                    None => println!("{:04}_{}\tpending", status.version, status.name),
+                    Some(applied_at) => println!(
+                        "{:04}_{}\tapplied at {}",
+                        status.version, status.name, applied_at
+                    ),
+                }
+            }
+        }
+        MigrationMode::DryRun => {
+            let pending = dry_run().await?;
+            if pending.is_empty() {
+                println!("No pending migrations.");
+            }
+            for migration in pending {
+                println!(
+                    "-- {:04}_{}\n{}",
+                    migration.version, migration.name, migration.query
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * A method to get the version of the last known migration.
+*/
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or_default()
+}
+
+/**
+ * A method to get the version of the last applied migration. Returns 0 if none was applied.
+ * Unlike the status, nothing is defined in the database.
+*/
+pub async fn get_applied_version() -> surrealdb::Result<u32> {
+    let version: Option<u32> = DATABASE
+        .query("SELECT VALUE version FROM _migrations ORDER BY version DESC LIMIT 1")
+        .await?
+        .take(0)?;
+    Ok(version.unwrap_or_default())
+}
+
+/**
+ * A method to get the migrations that weren't applied yet.
+ * Fails if some applied migration was changed since.
+*/
+async fn get_pending() -> Result<Vec<&'static Migration>, Box<dyn std::error::Error>> {
+    let applied = get_applied().await?;
+
+    // Check if the applied migrations are still the same.
+    for a in &applied {
+        match MIGRATIONS.iter().find(|m| m.version == a.version) {
+            None => warn!(
+                "The migration {:04} was applied, but it's unknown. Is this version outdated?",
+                a.version
+            ),
+            Some(migration) if migration.checksum() != a.checksum => {
+                return Err(format!(
+                    "The migration {:04}_{} was changed after being applied.",
+                    migration.version, migration.name
+                )
+                .into())
+            }
+            Some(_) => (),
+        }
+    }
+
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .collect())
+}
+
+/**
+ * A method to get the applied migrations. Defines the migrations table if needed.
+*/
+async fn get_applied() -> surrealdb::Result<Vec<AppliedMigration>> {
+    DATABASE
+        .query(
+            "
+                BEGIN TRANSACTION;
+                DEFINE TABLE _migrations SCHEMAFULL;
+                DEFINE FIELD version ON TABLE _migrations TYPE int;
+                DEFINE FIELD name ON TABLE _migrations TYPE string;
+                DEFINE FIELD checksum ON TABLE _migrations TYPE string;
+                DEFINE FIELD applied_at ON TABLE _migrations TYPE datetime;
+                COMMIT TRANSACTION;
+            ",
+        )
+        .await?
+        .check()?;
+
+    DATABASE
+        .query("SELECT version, checksum, applied_at FROM _migrations ORDER BY version")
+        .await?
+        .take(0)
+}
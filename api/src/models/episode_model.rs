@@ -0,0 +1,266 @@
+// Libs
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Id, Thing};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use super::model_trait::ModelTrait;
+use crate::database::DATABASE;
+use crate::metrics::observe_query;
+
+// Structs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Episode {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+    pub media: Thing,
+    pub season: u16,
+    pub number: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watched_at: Option<Datetime>,
+    pub created_at: Datetime,
+    pub updated_at: Datetime,
+}
+
+/**
+ * season: The season of the episode. The season 0 has the specials.
+ * watched: If the episode is watched. It's marked as watched when it's created.
+*/
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EpisodeRequest {
+    pub season: u16,
+    pub number: u16,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub watched: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EpisodeResponse {
+    pub id: String,
+    pub media: String,
+    pub season: u16,
+    pub number: u16,
+    pub title: Option<String>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub watched_at: Option<Datetime>,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: Datetime,
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: Datetime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WatchedRequest {
+    pub watched: bool,
+}
+
+// Implementations
+impl Episode {
+    /**
+     * A method to create a new episode of the media.
+     */
+    pub fn new(media: Thing, request: EpisodeRequest) -> Self {
+        let mut episode = Self {
+            id: None,
+            media,
+            season: request.season,
+            number: request.number,
+            title: request.title,
+            watched_at: None,
+            created_at: Datetime::default(),
+            updated_at: Datetime::default(),
+        };
+        episode.set_watched(request.watched);
+        episode
+    }
+
+    /**
+     * A method to get all the episodes of the media, sorted by season and number.
+     */
+    pub async fn from_media(media: &Thing) -> surrealdb::Result<Vec<Self>> {
+        info!("Getting the episodes of {}.", media);
+        let episodes: Vec<Self> = observe_query(
+            "episode",
+            "from_media",
+            DATABASE
+                .query("SELECT * FROM episode WHERE media = $media ORDER BY season ASC, number ASC")
+                .bind(("media", media)),
+        )
+        .await?
+        .take(0)?;
+
+        info!("{} episodes found in {}.", episodes.len(), media);
+        Ok(episodes)
+    }
+
+    /**
+     * A method to mark all the episodes of some season as watched or not.
+     * The episodes already watched keep when they were watched.
+     */
+    pub async fn set_season_watched(
+        media: &Thing,
+        season: u16,
+        watched: bool,
+    ) -> surrealdb::Result<()> {
+        info!(
+            "Marking the season {} of {} as watched: {}.",
+            season, media, watched
+        );
+        let query = match watched {
+            true => "UPDATE episode SET watched_at = time::now(), updated_at = time::now() WHERE media = $media AND season = $season AND watched_at IS NONE",
+            false => "UPDATE episode SET watched_at = NONE, updated_at = time::now() WHERE media = $media AND season = $season AND watched_at IS NOT NONE",
+        };
+        observe_query(
+            "episode",
+            "set_season_watched",
+            DATABASE
+                .query(query)
+                .bind(("media", media))
+                .bind(("season", season)),
+        )
+        .await?
+        .check()?;
+
+        Ok(())
+    }
+
+    /**
+     * A method to check if the episode was watched.
+     */
+    pub fn is_watched(&self) -> bool {
+        self.watched_at.is_some()
+    }
+
+    /**
+     * A method to mark the episode as watched or not. An episode already watched keeps when it was watched.
+     */
+    pub fn set_watched(&mut self, watched: bool) {
+        match watched {
+            true if self.watched_at.is_none() => self.watched_at = Some(Datetime::default()),
+            true => (),
+            false => self.watched_at = None,
+        }
+    }
+
+    /**
+     * A method to convert the current episode to a EpisodeResponse
+     */
+    pub fn to_episode_response(&self) -> EpisodeResponse {
+        EpisodeResponse::from(self.clone())
+    }
+}
+
+impl ModelTrait<Episode> for Episode {
+    async fn from_id(id: Id) -> surrealdb::Result<Option<Self>> {
+        // Create the thing.
+        let thing = Thing {
+            id,
+            tb: String::from("episode"),
+        };
+
+        // Get the episode.
+        info!("Getting {}.", &thing);
+        match observe_query(
+            "episode",
+            "from_id",
+            DATABASE.select::<Option<Self>>(thing.clone()),
+        )
+        .await?
+        {
+            None => {
+                info!("No {} found.", &thing);
+                Ok(None)
+            }
+            Some(episode) => {
+                info!("{} found.", thing);
+                Ok(Some(episode))
+            }
+        }
+    }
+
+    async fn sync(&mut self) -> surrealdb::Result<()> {
+        // Check if the episode already has an id. If not, generate a new one.
+        if self.id.is_none() {
+            return self.create().await;
+        }
+
+        // Sync the episode in the database.
+        self.updated_at = Datetime::default();
+        info!("Syncing {} in the database...", self.id.as_ref().unwrap());
+        observe_query(
+            "episode",
+            "sync",
+            DATABASE
+                .update::<Option<Self>>(("episode", self.id.clone().unwrap()))
+                .content(&self),
+        )
+        .await?;
+        info!("Synced {} in the database.", self.id.as_ref().unwrap());
+
+        Ok(())
+    }
+
+    async fn create(&mut self) -> surrealdb::Result<()> {
+        // Generate a new thing for the episode.
+        info!("Creating a new episode...");
+        self.id = Some(Self::generate_new_ulid("episode").await?);
+
+        // Create the episode in the database.
+        self.created_at = Datetime::default();
+        self.updated_at = self.created_at.clone();
+        let created_episodes = observe_query(
+            "episode",
+            "create",
+            DATABASE.create::<Vec<Self>>("episode").content(&self),
+        )
+        .await?;
+
+        // Check if it was really created.
+        if created_episodes.is_empty() {
+            warn!("No episode was created for {}.", self.id.as_ref().unwrap());
+        }
+
+        info!("The new {} was created.", self.id.as_ref().unwrap());
+        Ok(())
+    }
+
+    fn merge(&mut self, value: Self) {
+        // Merge the episode with another episode.
+        self.season = value.season;
+        self.number = value.number;
+        self.title = value.title;
+        self.watched_at = value.watched_at;
+    }
+
+    async fn delete(self) -> surrealdb::Result<()> {
+        // Check if the episode has an id.
+        if let Some(id) = self.id.clone() {
+            info!("Deleting {}...", &id);
+            observe_query("episode", "delete", DATABASE.delete::<Option<Episode>>(&id)).await?;
+            info!("The {} was deleted.", id);
+        } else {
+            warn!("The episode has no id.");
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Episode> for EpisodeResponse {
+    fn from(value: Episode) -> Self {
+        Self {
+            id: value.id.expect("Logic error.").id.to_string(),
+            media: value.media.id.to_string(),
+            season: value.season,
+            number: value.number,
+            title: value.title,
+            watched_at: value.watched_at,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
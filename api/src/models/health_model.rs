@@ -0,0 +1,93 @@
+// Libs
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::database::{database_state, DatabaseState, DATABASE};
+use crate::migrations::{get_applied_version, latest_version};
+
+// Data
+static READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Structs
+/**
+ * The readiness of the API. It's ready when the database answers and all the migrations were applied.
+*/
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(
+    description = "The API is ready when the database answers and all the migrations were applied."
+)]
+pub struct Readiness {
+    pub database: bool,
+    pub migration_version: Option<u32>,
+    pub expected_migration_version: u32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Version {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub commit: Option<&'static str>,
+    pub migration_version: u32,
+}
+
+// Implementations
+impl Readiness {
+    /**
+     * A method to ping the database and get the version of the applied migrations.
+     */
+    pub async fn check() -> Self {
+        let expected_migration_version = latest_version();
+        let check = async {
+            DATABASE.health().await?;
+            get_applied_version().await
+        };
+
+        let migration_version = match database_state() {
+            DatabaseState::Connected => {
+                match tokio::time::timeout(READINESS_TIMEOUT, check).await {
+                    Err(_) => {
+                        warn!("The database didn\'t answer the readiness check in time.");
+                        None
+                    }
+                    Ok(Err(e)) => {
+                        warn!("Couldn\'t check the readiness of the database. {}", e);
+                        None
+                    }
+                    Ok(Ok(version)) => Some(version),
+                }
+            }
+            _ => None,
+        };
+
+        Self {
+            database: migration_version.is_some(),
+            migration_version,
+            expected_migration_version,
+        }
+    }
+
+    /**
+     * A method to check if the API can receive requests.
+     */
+    pub fn is_ready(&self) -> bool {
+        self.database && self.migration_version >= Some(self.expected_migration_version)
+    }
+}
+
+impl Version {
+    /**
+     * A method to get the version of the running build.
+     * The commit is defined by the GIT_COMMIT env var when building.
+     */
+    pub fn current() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            commit: option_env!("GIT_COMMIT"),
+            migration_version: latest_version(),
+        }
+    }
+}
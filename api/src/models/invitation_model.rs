@@ -0,0 +1,269 @@
+// Libs
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Id, Thing};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use super::model_trait::ModelTrait;
+use super::watchlist_model::Role;
+use crate::database::DATABASE;
+use crate::metrics::observe_query;
+
+// Data
+static INVITATION_TTL_DAYS: i64 = 7;
+
+// Enums
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Expired,
+}
+
+// Structs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Invitation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+    pub watchlist: Thing,
+    pub inviter: Thing,
+    pub invitee: Thing,
+    pub role: Role,
+    pub status: InvitationStatus,
+    pub expires_at: Datetime,
+    pub created_at: Datetime,
+    pub updated_at: Datetime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationRequest {
+    pub username: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationResponse {
+    pub id: String,
+    pub watchlist: String,
+    pub inviter: String,
+    pub invitee: String,
+    pub role: Role,
+    pub status: InvitationStatus,
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: Datetime,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: Datetime,
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: Datetime,
+}
+
+// Implementations
+impl Invitation {
+    /**
+     * A method to create a new pending invitation.
+     */
+    pub fn new(watchlist: Thing, inviter: Thing, invitee: Thing, role: Role) -> Self {
+        Self {
+            id: None,
+            watchlist,
+            inviter,
+            invitee,
+            role,
+            status: InvitationStatus::Pending,
+            expires_at: Datetime::from(Utc::now() + Duration::days(INVITATION_TTL_DAYS)),
+            created_at: Datetime::default(),
+            updated_at: Datetime::default(),
+        }
+    }
+
+    /**
+     * A method to get all pending invitations sent to the user.
+     */
+    pub async fn get_pending_from_invitee(invitee: &Thing) -> surrealdb::Result<Vec<Self>> {
+        info!("Getting all pending invitations to {}.", invitee);
+        Self::expire_all().await?;
+        let invitations: Vec<Self> = observe_query(
+            "invitation",
+            "get_pending_from_invitee",
+            DATABASE
+                .query("SELECT * FROM invitation WHERE invitee = $invitee AND status = 'pending'")
+                .bind(("invitee", invitee)),
+        )
+        .await?
+        .take(0)?;
+
+        info!(
+            "{} pending invitations found to {}.",
+            invitations.len(),
+            invitee
+        );
+        Ok(invitations)
+    }
+
+    /**
+     * A method to check if the user already has a pending invitation to the watchlist.
+     */
+    pub async fn has_pending(watchlist: &Thing, invitee: &Thing) -> surrealdb::Result<bool> {
+        info!(
+            "Checking pending invitations from {} to {}.",
+            watchlist, invitee
+        );
+        Self::expire_all().await?;
+        let invitations: Vec<Self> = observe_query("invitation", "has_pending", DATABASE
+            .query("SELECT * FROM invitation WHERE watchlist = $watchlist AND invitee = $invitee AND status = 'pending'")
+            .bind(("watchlist", watchlist))
+            .bind(("invitee", invitee)))
+            .await?
+            .take(0)?;
+
+        Ok(!invitations.is_empty())
+    }
+
+    /**
+     * A method to mark all the pending invitations that passed their expiration as expired.
+     */
+    pub async fn expire_all() -> surrealdb::Result<()> {
+        info!("Expiring the old invitations...");
+        observe_query("invitation", "expire_all", DATABASE
+            .query("UPDATE invitation SET status = 'expired', updated_at = time::now() WHERE status = 'pending' AND expires_at <= time::now()"))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /**
+     * A method to check if the invitation passed its expiration.
+     */
+    pub fn is_expired(&self) -> bool {
+        self.status == InvitationStatus::Expired || *self.expires_at <= Utc::now()
+    }
+
+    /**
+     * A method to convert the current invitation to a InvitationResponse
+     */
+    pub fn to_invitation_response(&self) -> InvitationResponse {
+        InvitationResponse::from(self.clone())
+    }
+}
+
+impl ModelTrait<Invitation> for Invitation {
+    async fn from_id(id: Id) -> surrealdb::Result<Option<Self>> {
+        // Create the thing.
+        let thing = Thing {
+            id,
+            tb: String::from("invitation"),
+        };
+
+        // Get the invitation.
+        info!("Getting {}.", &thing);
+        match observe_query(
+            "invitation",
+            "from_id",
+            DATABASE.select::<Option<Self>>(thing.clone()),
+        )
+        .await?
+        {
+            None => {
+                info!("No {} found.", &thing);
+                Ok(None)
+            }
+            Some(invitation) => {
+                info!("{} found.", thing);
+                Ok(Some(invitation))
+            }
+        }
+    }
+
+    async fn sync(&mut self) -> surrealdb::Result<()> {
+        // Check if the invitation already has an id. If not, generate a new one.
+        if self.id.is_none() {
+            return self.create().await;
+        }
+
+        // Sync the invitation in the database.
+        self.updated_at = Datetime::default();
+        info!("Syncing {} in the database...", self.id.as_ref().unwrap());
+        observe_query(
+            "invitation",
+            "sync",
+            DATABASE
+                .update::<Option<Self>>(("invitation", self.id.clone().unwrap()))
+                .content(&self),
+        )
+        .await?;
+        info!("Synced {} in the database.", self.id.as_ref().unwrap());
+
+        Ok(())
+    }
+
+    async fn create(&mut self) -> surrealdb::Result<()> {
+        // Generate a new thing for the invitation.
+        info!("Creating a new invitation...");
+        self.id = Some(Self::generate_new_ulid("invitation").await?);
+
+        // Create the invitation in the database.
+        self.created_at = Datetime::default();
+        self.updated_at = self.created_at.clone();
+        let created_invitations = observe_query(
+            "invitation",
+            "create",
+            DATABASE.create::<Vec<Self>>("invitation").content(&self),
+        )
+        .await?;
+
+        // Check if it was really created.
+        if created_invitations.is_empty() {
+            warn!(
+                "No invitation was created for {}.",
+                self.id.as_ref().unwrap()
+            );
+        }
+
+        info!("The new {} was created.", self.id.as_ref().unwrap());
+        Ok(())
+    }
+
+    fn merge(&mut self, value: Self) {
+        // Merge the invitation with another invitation.
+        self.role = value.role;
+        self.status = value.status;
+    }
+
+    async fn delete(self) -> surrealdb::Result<()> {
+        // Check if the invitation has an id.
+        if let Some(id) = self.id.clone() {
+            info!("Deleting {}...", &id);
+            observe_query(
+                "invitation",
+                "delete",
+                DATABASE.delete::<Option<Invitation>>(&id),
+            )
+            .await?;
+            info!("The {} was deleted.", id);
+        } else {
+            warn!("The invitation has no id.");
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(value: Invitation) -> Self {
+        Self {
+            id: value.id.expect("Logic error.").id.to_string(),
+            watchlist: value.watchlist.id.to_string(),
+            inviter: value.inviter.id.to_string(),
+            invitee: value.invitee.id.to_string(),
+            role: value.role,
+            status: value.status,
+            expires_at: value.expires_at,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
@@ -2,11 +2,54 @@
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::{Datetime, Id, Thing};
 use tracing::{info, warn};
+use utoipa::{IntoParams, ToSchema};
 
+use super::episode_model::Episode;
 use super::model_trait::ModelTrait;
 use crate::database::DATABASE;
+use crate::metrics::observe_query;
+
+// Data
+pub static MEDIA_PAGE_DEFAULT_LIMIT: usize = 20;
+pub static MEDIA_PAGE_MAX_LIMIT: usize = 100;
+
+// Enums
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaSort {
+    Title,
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    #[default]
+    Movie,
+    Series,
+    Anime,
+    Documentary,
+    Book,
+    Game,
+}
 
 // Structs
+/**
+ * The ids of the media in the external databases.
+ * tmdb: The id in the TMDB, unique by the kind of the media.
+*/
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct ExternalIds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imdb: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anilist: Option<u32>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Media {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -15,31 +58,267 @@ pub struct Media {
     pub description: String,
     pub watchlist: Thing,
     pub watched: bool,
+    pub kind: MediaKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<u16>,
+    pub genres: Vec<String>,
+    pub external_ids: ExternalIds,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<MediaProgress>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<Datetime>,
     pub created_at: Datetime,
     pub updated_at: Datetime,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/**
+ * The watched episodes of a media. It's refreshed whenever its episodes change.
+ * last_season, last_number: The last watched episode.
+*/
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MediaProgress {
+    pub watched: u32,
+    pub total: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_season: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_number: Option<u16>,
+}
+
+/**
+ * The details are optional, so the clients without them still work. The kind defaults to movie.
+ * runtime: The runtime in minutes.
+ * watched: Ignored by the series and animes with episodes, as it's derived from them.
+*/
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct MediaRequest {
     pub title: String,
     pub description: String,
     pub watchlist: String,
     pub watched: bool,
+    #[serde(default)]
+    pub kind: MediaKind,
+    #[serde(default)]
+    pub year: Option<u16>,
+    #[serde(default)]
+    pub runtime: Option<u16>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    #[serde(default)]
+    pub external_ids: ExternalIds,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct MediaResponse {
     pub id: String,
     pub title: String,
     pub description: String,
     pub watchlist: String,
     pub watched: bool,
+    pub kind: MediaKind,
+    pub year: Option<u16>,
+    pub runtime: Option<u16>,
+    pub genres: Vec<String>,
+    pub external_ids: ExternalIds,
+    pub progress: Option<ProgressResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub deleted_at: Option<Datetime>,
+    #[schema(value_type = String, format = DateTime)]
     pub created_at: Datetime,
+    #[schema(value_type = String, format = DateTime)]
     pub updated_at: Datetime,
 }
 
+/**
+ * The progress of a media with episodes, like "S02E05, 43%".
+ * last_watched: The last watched episode, like "S02E05".
+ * percent: The percent of watched episodes, rounded down.
+*/
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProgressResponse {
+    pub watched: u32,
+    pub total: u32,
+    pub percent: u32,
+    pub last_watched: Option<String>,
+    pub summary: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MediaQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub sort: MediaSort,
+    pub watched: Option<bool>,
+    pub q: Option<String>,
+    pub kind: Option<MediaKind>,
+    pub genre: Option<String>,
+    pub year_from: Option<u16>,
+    pub year_to: Option<u16>,
+}
+
+/**
+ * The position after the last media of a page. Sent to the client as an opaque hex string.
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MediaCursor {
+    pub sort: MediaSort,
+    pub value: String,
+    pub id: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct MediaPage {
+    pub items: Vec<Media>,
+    pub next_cursor: Option<String>,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MediaPageResponse {
+    pub items: Vec<MediaResponse>,
+    pub next_cursor: Option<String>,
+    pub total: usize,
+}
+
 // Implementations
+impl MediaSort {
+    /**
+     * A method to get the expression used to sort the medias.
+     */
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Title => "string::lowercase(title)",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+        }
+    }
+
+    /**
+     * A method to get the expression to compare the sort key with the cursor value.
+     */
+    pub fn cursor_value(&self) -> &'static str {
+        match self {
+            Self::Title => "$cursor_value",
+            Self::CreatedAt | Self::UpdatedAt => "<datetime> $cursor_value",
+        }
+    }
+}
+
+impl MediaKind {
+    /**
+     * A method to check if the medias of the kind have episodes. Their watched is derived from the episodes.
+     */
+    pub fn is_episodic(&self) -> bool {
+        matches!(self, Self::Series | Self::Anime)
+    }
+}
+
+impl MediaProgress {
+    /**
+     * A method to get the progress of some episodes. None if there's no episode.
+     */
+    pub fn from_episodes(episodes: &[Episode]) -> Option<Self> {
+        if episodes.is_empty() {
+            return None;
+        }
+
+        let watched: Vec<&Episode> = episodes.iter().filter(|e| e.is_watched()).collect();
+        let last = watched.iter().max_by_key(|e| (e.season, e.number));
+        Some(Self {
+            watched: watched.len() as u32,
+            total: episodes.len() as u32,
+            last_season: last.map(|e| e.season),
+            last_number: last.map(|e| e.number),
+        })
+    }
+}
+
+impl MediaCursor {
+    /**
+     * A method to create the cursor pointing after the given media.
+     */
+    pub fn after(media: &Media, sort: MediaSort) -> Self {
+        let value = match sort {
+            MediaSort::Title => media.title.to_lowercase(),
+            MediaSort::CreatedAt => media.created_at.to_raw(),
+            MediaSort::UpdatedAt => media.updated_at.to_raw(),
+        };
+        Self {
+            sort,
+            value,
+            id: media.id.as_ref().expect("Logic error.").id.to_raw(),
+        }
+    }
+
+    /**
+     * A method to encode the cursor to send it to the client.
+     */
+    pub fn encode(&self) -> String {
+        hex::encode(serde_json::to_vec(self).expect("Logic error."))
+    }
+
+    /**
+     * A method to decode a cursor received from the client.
+     */
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = hex::decode(cursor).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+impl MediaPage {
+    /**
+     * A method to convert the current page to a MediaPageResponse
+     */
+    pub fn to_media_page_response(&self) -> MediaPageResponse {
+        MediaPageResponse::from(self.clone())
+    }
+}
+
 impl Media {
+    /**
+     * A method to check if the media is in the trash.
+     */
+    pub fn is_trashed(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /**
+     * A method to move the media to the trash. It's purged after the retention period.
+     */
+    pub async fn trash(&mut self) -> surrealdb::Result<()> {
+        info!("Moving {} to the trash...", self.id.as_ref().unwrap());
+        self.deleted_at = Some(Datetime::default());
+        self.sync().await
+    }
+
+    /**
+     * A method to restore the media from the trash.
+     */
+    pub async fn restore(&mut self) -> surrealdb::Result<()> {
+        info!("Restoring {} from the trash...", self.id.as_ref().unwrap());
+        self.deleted_at = None;
+        self.sync().await
+    }
+
+    /**
+     * A method to refresh the progress from the episodes and sync the media.
+     * The series and animes with episodes are watched when all their episodes are.
+     */
+    pub async fn sync_progress(&mut self) -> surrealdb::Result<()> {
+        let episodes = Episode::from_media(self.id.as_ref().unwrap()).await?;
+        self.progress = MediaProgress::from_episodes(&episodes);
+        if let Some(progress) = self.progress.as_ref().filter(|_| self.kind.is_episodic()) {
+            self.watched = progress.watched == progress.total;
+        }
+        self.sync().await
+    }
+
     /**
      * A method to convert the current media to a MediaResponse
      */
@@ -58,7 +337,13 @@ impl ModelTrait<Media> for Media {
 
         // Get the media.
         info!("Getting {}.", &thing);
-        match DATABASE.select::<Option<Self>>(thing.clone()).await? {
+        match observe_query(
+            "media",
+            "from_id",
+            DATABASE.select::<Option<Self>>(thing.clone()),
+        )
+        .await?
+        {
             None => {
                 info!("No {} found.", &thing);
                 Ok(None)
@@ -70,28 +355,6 @@ impl ModelTrait<Media> for Media {
         }
     }
 
-    async fn migration() -> surrealdb::Result<()> {
-        // Define the media table.
-        info!("Running Media migration...");
-        DATABASE
-                .query(
-                    "
-                        BEGIN TRANSACTION;
-                        DEFINE TABLE media SCHEMAFULL;
-                        DEFINE FIELD title ON TABLE media TYPE string ASSERT $value = /^[a-zA-Z0-9!@#$%&*_\\-+.,<>;\\/? ]{3,20}$/;
-                        DEFINE FIELD description ON TABLE media TYPE string ASSERT $value = /^[a-zA-Z0-9!@#$%&*_\\-+.,<>;\\/? ]{3,60}$/;
-                        DEFINE FIELD watchlist ON TABLE media TYPE record<watchlist>;
-                        DEFINE FIELD watched ON TABLE media TYPE bool;
-                        DEFINE FIELD created_at ON TABLE media TYPE datetime;
-                        DEFINE FIELD updated_at ON TABLE media TYPE datetime;
-                        COMMIT TRANSACTION;
-                    ",
-                )
-                .await?;
-
-        Ok(())
-    }
-
     async fn sync(&mut self) -> surrealdb::Result<()> {
         // Check if the media already has an id. If not, generate a new one.
         if self.id.is_none() {
@@ -101,10 +364,14 @@ impl ModelTrait<Media> for Media {
         // Sync the media in the database.
         self.updated_at = Datetime::default();
         info!("Syncing {} in the database...", self.id.as_ref().unwrap());
-        DATABASE
-            .update::<Option<Self>>(("media", self.id.clone().unwrap()))
-            .content(&self)
-            .await?;
+        observe_query(
+            "media",
+            "sync",
+            DATABASE
+                .update::<Option<Self>>(("media", self.id.clone().unwrap()))
+                .content(&self),
+        )
+        .await?;
         info!("Synced {} in the database.", self.id.as_ref().unwrap());
 
         Ok(())
@@ -118,7 +385,12 @@ impl ModelTrait<Media> for Media {
         // Create the media in the database.
         self.created_at = Datetime::default();
         self.updated_at = self.created_at.clone();
-        let created_media = DATABASE.create::<Vec<Self>>("media").content(&self).await?;
+        let created_media = observe_query(
+            "media",
+            "create",
+            DATABASE.create::<Vec<Self>>("media").content(&self),
+        )
+        .await?;
 
         // Check if it was really created.
         if created_media.is_empty() {
@@ -136,13 +408,34 @@ impl ModelTrait<Media> for Media {
         self.description = value.description;
         self.watchlist = value.watchlist;
         self.watched = value.watched;
+        self.kind = value.kind;
+        self.year = value.year;
+        self.runtime = value.runtime;
+        self.genres = value.genres;
+        self.external_ids = value.external_ids;
     }
 
     async fn delete(self) -> surrealdb::Result<()> {
         // Check if the media has an id.
+        // Its episodes are deleted with it.
         if let Some(id) = self.id.clone() {
             info!("Deleting {}...", &id);
-            DATABASE.delete::<Option<Media>>(&id).await?;
+            observe_query(
+                "media",
+                "delete",
+                DATABASE
+                    .query(
+                        "
+                        BEGIN TRANSACTION;
+                        DELETE episode WHERE media = $media;
+                        DELETE $media;
+                        COMMIT TRANSACTION;
+                    ",
+                    )
+                    .bind(("media", &id)),
+            )
+            .await?
+            .check()?;
             info!("The {} was deleted.", id);
         } else {
             warn!("The media has no id.");
@@ -164,6 +457,13 @@ impl From<MediaRequest> for Media {
                 tb: String::from("watchlist"),
             },
             watched: value.watched,
+            kind: value.kind,
+            year: value.year,
+            runtime: value.runtime,
+            genres: value.genres,
+            external_ids: value.external_ids,
+            progress: None,
+            deleted_at: None,
             created_at: Datetime::default(),
             updated_at: Datetime::default(),
         }
@@ -178,8 +478,46 @@ impl From<Media> for MediaResponse {
             description: value.description,
             watchlist: value.watchlist.id.to_string(),
             watched: value.watched,
+            kind: value.kind,
+            year: value.year,
+            runtime: value.runtime,
+            genres: value.genres,
+            external_ids: value.external_ids,
+            progress: value.progress.map(ProgressResponse::from),
+            deleted_at: value.deleted_at,
             created_at: value.created_at,
             updated_at: value.updated_at,
         }
     }
 }
+
+impl From<MediaProgress> for ProgressResponse {
+    fn from(value: MediaProgress) -> Self {
+        let percent = value.watched * 100 / value.total.max(1);
+        let last_watched = match (value.last_season, value.last_number) {
+            (Some(season), Some(number)) => Some(format!("S{:02}E{:02}", season, number)),
+            _ => None,
+        };
+        let summary = match &last_watched {
+            Some(last_watched) => format!("{}, {}%", last_watched, percent),
+            None => format!("{}%", percent),
+        };
+        Self {
+            watched: value.watched,
+            total: value.total,
+            percent,
+            last_watched,
+            summary,
+        }
+    }
+}
+
+impl From<MediaPage> for MediaPageResponse {
+    fn from(value: MediaPage) -> Self {
+        Self {
+            items: value.items.into_iter().map(MediaResponse::from).collect(),
+            next_cursor: value.next_cursor,
+            total: value.total,
+        }
+    }
+}
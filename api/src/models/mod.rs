@@ -1,4 +1,9 @@
+pub mod episode_model;
+pub mod health_model;
+pub mod invitation_model;
 pub mod media_model;
 pub mod model_trait;
+pub mod search_model;
+pub mod trash_model;
 pub mod user_model;
 pub mod watchlist_model;
@@ -3,17 +3,12 @@ use surrealdb::sql::{Id, Thing};
 use tracing::info;
 
 // Traits
-pub trait ModelTrait<T> {
+pub(crate) trait ModelTrait<T> {
     /**
      * A method to get some T using a id.
      */
     async fn from_id(id: Id) -> surrealdb::Result<Option<T>>;
 
-    /**
-     * A method to run the migration.
-     */
-    async fn migration() -> surrealdb::Result<()>;
-
     /**
      * A method to syncronize the T in the database.
      */
@@ -0,0 +1,186 @@
+// Libs
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use tracing::info;
+use utoipa::{IntoParams, ToSchema};
+
+use super::{
+    media_model::{Media, MediaResponse},
+    watchlist_model::{Watchlist, WatchlistResponse},
+};
+use crate::database::DATABASE;
+use crate::metrics::observe_query;
+
+// Data
+pub static SEARCH_DEFAULT_LIMIT: usize = 20;
+pub static SEARCH_MAX_LIMIT: usize = 100;
+pub static SEARCH_MAX_QUERY_LENGTH: usize = 60;
+/**
+ * The private use characters around the matched terms. The texts can't have them, unlike `<` or `>`.
+ * They're replaced by `<mark>` and `</mark>` once the texts are escaped.
+*/
+static HIGHLIGHT_OPEN: char = '\u{E000}';
+static HIGHLIGHT_CLOSE: char = '\u{E001}';
+
+// Structs
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MediaHit {
+    #[serde(flatten)]
+    pub media: Media,
+    pub score: f64,
+    pub title_highlight: String,
+    pub description_highlight: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WatchlistHit {
+    #[serde(flatten)]
+    pub watchlist: Watchlist,
+    pub score: f64,
+    pub title_highlight: String,
+    pub description_highlight: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub watchlists: Vec<WatchlistHit>,
+    pub medias: Vec<MediaHit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Highlights {
+    pub title: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MediaHitResponse {
+    #[serde(flatten)]
+    pub media: MediaResponse,
+    pub score: f64,
+    pub highlights: Highlights,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WatchlistHitResponse {
+    #[serde(flatten)]
+    pub watchlist: WatchlistResponse,
+    pub score: f64,
+    pub highlights: Highlights,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SearchResponse {
+    pub watchlists: Vec<WatchlistHitResponse>,
+    pub medias: Vec<MediaHitResponse>,
+}
+
+// Implementations
+impl SearchResult {
+    /**
+     * A method to search the titles and descriptions of the given watchlists and their medias.
+     * The hits are ranked by their BM25 score and the matched terms are highlighted, see `highlight`.
+     */
+    pub async fn search(q: &str, watchlists: &[Thing], limit: usize) -> surrealdb::Result<Self> {
+        info!("Searching in {} watchlists...", watchlists.len());
+        let mut response = observe_query("search", "search", DATABASE
+            .query("SELECT *, search::score(1) + search::score(2) AS score, search::highlight($open, $close, 1) AS title_highlight, search::highlight($open, $close, 2) AS description_highlight FROM watchlist WHERE (title @1@ $q OR description @2@ $q) AND id INSIDE $watchlists ORDER BY score DESC LIMIT $limit")
+            .query("SELECT *, search::score(1) + search::score(2) AS score, search::highlight($open, $close, 1) AS title_highlight, search::highlight($open, $close, 2) AS description_highlight FROM media WHERE (title @1@ $q OR description @2@ $q) AND watchlist INSIDE $watchlists AND deleted_at IS NONE ORDER BY score DESC LIMIT $limit")
+            .bind(("q", q))
+            .bind(("watchlists", watchlists))
+            .bind(("limit", limit))
+            .bind(("open", HIGHLIGHT_OPEN.to_string()))
+            .bind(("close", HIGHLIGHT_CLOSE.to_string())))
+            .await?;
+
+        let result = Self {
+            watchlists: response.take(0)?,
+            medias: response.take(1)?,
+        };
+        info!(
+            "{} watchlists and {} medias found.",
+            result.watchlists.len(),
+            result.medias.len()
+        );
+        Ok(result)
+    }
+
+    /**
+     * A method to convert the current result to a SearchResponse
+     */
+    pub fn to_search_response(&self) -> SearchResponse {
+        SearchResponse::from(self.clone())
+    }
+}
+
+impl From<MediaHit> for MediaHitResponse {
+    fn from(value: MediaHit) -> Self {
+        Self {
+            media: MediaResponse::from(value.media),
+            score: value.score,
+            highlights: Highlights {
+                title: highlight(&value.title_highlight),
+                description: highlight(&value.description_highlight),
+            },
+        }
+    }
+}
+
+impl From<WatchlistHit> for WatchlistHitResponse {
+    fn from(value: WatchlistHit) -> Self {
+        Self {
+            watchlist: WatchlistResponse::from(value.watchlist),
+            score: value.score,
+            highlights: Highlights {
+                title: highlight(&value.title_highlight),
+                description: highlight(&value.description_highlight),
+            },
+        }
+    }
+}
+
+impl From<SearchResult> for SearchResponse {
+    fn from(value: SearchResult) -> Self {
+        Self {
+            watchlists: value
+                .watchlists
+                .into_iter()
+                .map(WatchlistHitResponse::from)
+                .collect(),
+            medias: value
+                .medias
+                .into_iter()
+                .map(MediaHitResponse::from)
+                .collect(),
+        }
+    }
+}
+
+// Functions
+/**
+ * A method to get the HTML of some highlighted text.
+ * The text is escaped, then the matched terms are wrapped in `<mark>`.
+*/
+fn highlight(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c if c == HIGHLIGHT_OPEN => html.push_str("<mark>"),
+            c if c == HIGHLIGHT_CLOSE => html.push_str("</mark>"),
+            c => html.push(c),
+        }
+    }
+    html
+}
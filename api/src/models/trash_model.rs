@@ -0,0 +1,113 @@
+// Libs
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+use tracing::info;
+use utoipa::ToSchema;
+
+use super::{
+    media_model::{Media, MediaResponse},
+    watchlist_model::{Watchlist, WatchlistResponse},
+};
+use crate::database::DATABASE;
+use crate::metrics::observe_query;
+
+// Structs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Trash {
+    pub watchlists: Vec<Watchlist>,
+    pub medias: Vec<Media>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TrashResponse {
+    pub watchlists: Vec<WatchlistResponse>,
+    pub medias: Vec<MediaResponse>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PurgeReport {
+    pub watchlists: usize,
+    pub medias: usize,
+}
+
+// Implementations
+impl Trash {
+    /**
+     * A method to get the trash of the user.
+     * It has the trashed watchlists the user owns and the trashed medias from the watchlists the user manages.
+     */
+    pub async fn from_user(user_id: &Thing) -> surrealdb::Result<Self> {
+        info!("Getting the trash from {}.", user_id);
+        let mut response = observe_query("trash", "from_user", DATABASE
+            .query("SELECT * FROM watchlist WHERE owner = $user AND deleted_at IS NOT NONE")
+            .query("SELECT * FROM media WHERE deleted_at IS NOT NONE AND watchlist.deleted_at IS NONE AND (watchlist.owner = $user OR watchlist.members[WHERE user = $user AND role = 'admin'])")
+            .bind(("user", user_id)))
+            .await?;
+
+        let trash = Self {
+            watchlists: response.take(0)?,
+            medias: response.take(1)?,
+        };
+        info!(
+            "{} watchlists and {} medias found in the trash from {}.",
+            trash.watchlists.len(),
+            trash.medias.len(),
+            user_id
+        );
+        Ok(trash)
+    }
+
+    /**
+     * A method to delete everything trashed before the given datetime.
+     * The medias and invitations of the purged watchlists and the episodes of the purged medias are deleted with them.
+     */
+    pub async fn purge(before: Datetime) -> surrealdb::Result<PurgeReport> {
+        info!("Purging the trash before {}...", before);
+        let report: Option<PurgeReport> = observe_query("trash", "purge", DATABASE
+            .query(
+                "
+                    BEGIN TRANSACTION;
+                    LET $watchlists = (SELECT VALUE id FROM watchlist WHERE deleted_at IS NOT NONE AND deleted_at < $before);
+                    LET $medias = (DELETE media WHERE (deleted_at IS NOT NONE AND deleted_at < $before) OR watchlist INSIDE $watchlists RETURN BEFORE);
+                    DELETE episode WHERE media INSIDE $medias.id;
+                    DELETE invitation WHERE watchlist INSIDE $watchlists;
+                    DELETE watchlist WHERE id INSIDE $watchlists;
+                    RETURN {
+                        watchlists: array::len($watchlists),
+                        medias: array::len($medias),
+                    };
+                    COMMIT TRANSACTION;
+                ",
+            )
+            .bind(("before", before)))
+            .await?
+            .take(0)?;
+
+        let report = report.unwrap_or_default();
+        info!(
+            "Purged {} watchlists and {} medias from the trash.",
+            report.watchlists, report.medias
+        );
+        Ok(report)
+    }
+
+    /**
+     * A method to convert the current trash to a TrashResponse
+     */
+    pub fn to_trash_response(&self) -> TrashResponse {
+        TrashResponse::from(self.clone())
+    }
+}
+
+impl From<Trash> for TrashResponse {
+    fn from(value: Trash) -> Self {
+        Self {
+            watchlists: value
+                .watchlists
+                .into_iter()
+                .map(WatchlistResponse::from)
+                .collect(),
+            medias: value.medias.into_iter().map(MediaResponse::from).collect(),
+        }
+    }
+}
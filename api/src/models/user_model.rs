@@ -2,11 +2,16 @@
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::{Datetime, Id, Thing};
 use tracing::{info, warn};
+use utoipa::ToSchema;
 
 use super::model_trait::ModelTrait;
 use super::watchlist_model::Watchlist;
 use crate::database::DATABASE;
-use crate::security::get_sha512;
+use crate::metrics::observe_query;
+use crate::security::{
+    generate_token_pair, hash_password, needs_rehash, verify_password, TokenPair,
+};
+use crate::validation::{normalize, username_key};
 
 // Structs
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -14,41 +19,57 @@ pub struct User {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<Thing>,
     pub username: String,
+    pub username_key: String,
 
     pub password: String,
+    #[serde(default)]
+    pub token_version: u32,
 
     pub created_at: Datetime,
     pub updated_at: Datetime,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct UserRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct UserResponse {
     pub id: String,
     pub username: String,
+    #[schema(value_type = String, format = DateTime)]
     pub created_at: Datetime,
+    #[schema(value_type = String, format = DateTime)]
     pub updated_at: Datetime,
 }
 
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct UserLoginResponse {
+    pub user: UserResponse,
+    #[serde(flatten)]
+    pub tokens: TokenPair,
+}
+
 // Implementations
 impl User {
     /**
-     * A method to get an user by username.
-     * The username need to be checked from security, BEFORE calling this function.
+     * A method to get an user by username. The users are found by the key of the username.
+     * The username need to be checked from the validation, BEFORE calling this function.
      */
     pub async fn from_username(username: &str) -> surrealdb::Result<Option<Self>> {
         // Get the user.
         info!("Getting user {}.", username);
-        match DATABASE
-            .query("SELECT * FROM user WHERE username = $username")
-            .bind(("username", username.to_lowercase()))
-            .await?
-            .take(0)
+        match observe_query(
+            "user",
+            "from_username",
+            DATABASE
+                .query("SELECT * FROM user WHERE username_key = $username_key")
+                .bind(("username_key", username_key(username))),
+        )
+        .await?
+        .take(0)
         {
             Err(e) => {
                 warn!("Couldn\'t get the user. {}", e);
@@ -65,23 +86,39 @@ impl User {
         }
     }
 
+    /**
+     * A method to get the token version of some user. Returns None if the user doesn't exist.
+     */
+    pub async fn get_token_version(user_id: &Thing) -> surrealdb::Result<Option<u32>> {
+        info!("Getting the token version of {}.", user_id);
+        observe_query(
+            "user",
+            "get_token_version",
+            DATABASE
+                .query("SELECT VALUE token_version FROM $user")
+                .bind(("user", user_id)),
+        )
+        .await?
+        .take(0)
+    }
+
     /**
      * A method to get all watchlist the user OWNS.
      */
-    pub async fn get_watchlists_as_owner(&self) -> surrealdb::Result<Vec<Watchlist>> {
+    pub async fn get_watchlists_as_owner(user_id: &Thing) -> surrealdb::Result<Vec<Watchlist>> {
         // Get the watchlists.
-        info!("Getting all watchlists from {}.", self.id.as_ref().unwrap());
-        let watchlists: Vec<Watchlist> = DATABASE
-            .query("SELECT * FROM watchlist WHERE owner = $owner")
-            .bind(("owner", self.id.as_ref().unwrap()))
-            .await?
-            .take(0)?;
-
-        info!(
-            "{} watchlists found for {}.",
-            watchlists.len(),
-            self.id.as_ref().unwrap()
-        );
+        info!("Getting all watchlists from {}.", user_id);
+        let watchlists: Vec<Watchlist> = observe_query(
+            "user",
+            "get_watchlists_as_owner",
+            DATABASE
+                .query("SELECT * FROM watchlist WHERE owner = $owner AND deleted_at IS NONE")
+                .bind(("owner", user_id)),
+        )
+        .await?
+        .take(0)?;
+
+        info!("{} watchlists found for {}.", watchlists.len(), user_id);
 
         Ok(watchlists)
     }
@@ -89,22 +126,19 @@ impl User {
     /**
      * A method to get all watchlist the user is a member.
      */
-    pub async fn get_watchlists_as_member(&self) -> surrealdb::Result<Vec<Watchlist>> {
+    pub async fn get_watchlists_as_member(user_id: &Thing) -> surrealdb::Result<Vec<Watchlist>> {
         // Get the watchlists.
-        info!(
-            "Getting all watchlists as member from {}.",
-            self.id.as_ref().unwrap()
-        );
-        let watchlists: Vec<Watchlist> = DATABASE
-            .query("SELECT * FROM watchlist WHERE members CONTAINS $member_id")
-            .bind(("member_id", self.id.as_ref().unwrap()))
+        info!("Getting all watchlists as member from {}.", user_id);
+        let watchlists: Vec<Watchlist> = observe_query("user", "get_watchlists_as_member", DATABASE
+            .query("SELECT * FROM watchlist WHERE members.user CONTAINS $member_id AND deleted_at IS NONE")
+            .bind(("member_id", user_id)))
             .await?
             .take(0)?;
 
         info!(
             "{} watchlists as member found for {}.",
             watchlists.len(),
-            self.id.as_ref().unwrap()
+            user_id
         );
 
         Ok(watchlists)
@@ -112,14 +146,27 @@ impl User {
 
     /**
      * A method to check if the login is valid for the user.
+     * If the stored hash is outdated, the password is rehashed and synced in the database.
      */
-    pub fn is_login_valid(&self, passwd: String) -> bool {
+    pub async fn is_login_valid(&mut self, passwd: String) -> bool {
         // Check if the password are equals.
-        if self.password != get_sha512(passwd.as_bytes()) {
+        if !verify_password(passwd.as_bytes(), &self.password) {
             info!("The user exists but the password is wrong.");
             return false;
         }
 
+        // Upgrade the stored hash.
+        if needs_rehash(&self.password) {
+            info!(
+                "Upgrading the password hash of {}...",
+                self.id.as_ref().unwrap()
+            );
+            self.password = hash_password(passwd.as_bytes());
+            if let Err(e) = self.sync().await {
+                warn!("Couldn\'t upgrade the password hash. {}", e);
+            }
+        }
+
         true
     }
 
@@ -129,6 +176,17 @@ impl User {
     pub fn to_user_response(&self) -> UserResponse {
         UserResponse::from(self.clone())
     }
+
+    /**
+     * A method to convert the current User to a UserLoginResponse, generating a new token pair.
+     */
+    pub fn to_user_login_response(&self) -> UserLoginResponse {
+        let user = self.to_user_response();
+        UserLoginResponse {
+            tokens: generate_token_pair(&user.id, self.token_version),
+            user,
+        }
+    }
 }
 
 impl ModelTrait<User> for User {
@@ -141,7 +199,13 @@ impl ModelTrait<User> for User {
 
         // Get the user.
         info!("Getting {}.", &thing);
-        match DATABASE.select::<Option<Self>>(thing.clone()).await? {
+        match observe_query(
+            "user",
+            "from_id",
+            DATABASE.select::<Option<Self>>(thing.clone()),
+        )
+        .await?
+        {
             None => {
                 info!("No {} found.", &thing);
                 Ok(None)
@@ -154,27 +218,6 @@ impl ModelTrait<User> for User {
         }
     }
 
-    async fn migration() -> surrealdb::Result<()> {
-        // Define the users table.
-        info!("Running User migration...");
-        DATABASE
-            .query(
-                "
-                    BEGIN TRANSACTION;
-                    DEFINE TABLE user SCHEMAFULL;
-                    DEFINE FIELD username ON TABLE user TYPE string VALUE string::lowercase($value) ASSERT $value = /^[a-z0-9!@#$%&*_\\-+.,<>;\\/? ]{3,20}$/;
-                    DEFINE INDEX usernameIndex ON TABLE user COLUMNS username UNIQUE;
-                    DEFINE FIELD password ON TABLE user TYPE string ASSERT $value = /^[a-z0-9]{128}$/;
-                    DEFINE FIELD created_at ON TABLE user TYPE datetime;
-                    DEFINE FIELD updated_at ON TABLE user TYPE datetime;
-                    COMMIT TRANSACTION;
-                ",
-            )
-            .await?;
-
-        Ok(())
-    }
-
     async fn sync(&mut self) -> surrealdb::Result<()> {
         // Check if the user already has an id. If not, generate a new one.
         if self.id.is_none() {
@@ -184,10 +227,14 @@ impl ModelTrait<User> for User {
         // Sync the user in the database.
         self.updated_at = Datetime::default();
         info!("Syncing {} in the database...", self.id.as_ref().unwrap());
-        DATABASE
-            .update::<Option<Self>>(("user", self.id.clone().unwrap()))
-            .content(&self)
-            .await?;
+        observe_query(
+            "user",
+            "sync",
+            DATABASE
+                .update::<Option<Self>>(("user", self.id.clone().unwrap()))
+                .content(&self),
+        )
+        .await?;
         info!("Synced {} in the database.", self.id.as_ref().unwrap());
 
         Ok(())
@@ -201,7 +248,12 @@ impl ModelTrait<User> for User {
         // Create the user in the database.
         self.created_at = Datetime::default();
         self.updated_at = self.created_at.clone();
-        let created_users = DATABASE.create::<Vec<Self>>("user").content(&self).await?;
+        let created_users = observe_query(
+            "user",
+            "create",
+            DATABASE.create::<Vec<Self>>("user").content(&self),
+        )
+        .await?;
 
         // Check if it was really created.
         if created_users.is_empty() {
@@ -216,14 +268,32 @@ impl ModelTrait<User> for User {
     fn merge(&mut self, value: Self) {
         // Merge the user with another user.
         self.username = value.username;
+        self.username_key = value.username_key;
         self.password = value.password;
     }
 
     async fn delete(self) -> surrealdb::Result<()> {
         // Check if the user has an id.
+        // The owned watchlists are deleted and the user leaves the other watchlists.
         if let Some(id) = self.id.clone() {
             info!("Deleting {}...", &id);
-            DATABASE.delete::<Option<User>>(&id).await?;
+            observe_query("user", "delete", DATABASE
+                .query(
+                    "
+                        BEGIN TRANSACTION;
+                        LET $watchlists = (SELECT VALUE id FROM watchlist WHERE owner = $user);
+                        DELETE episode WHERE media.watchlist INSIDE $watchlists;
+                        DELETE media WHERE watchlist INSIDE $watchlists;
+                        DELETE invitation WHERE watchlist INSIDE $watchlists OR inviter = $user OR invitee = $user;
+                        DELETE watchlist WHERE owner = $user;
+                        UPDATE watchlist SET members = members[WHERE user != $user] WHERE members.user CONTAINS $user;
+                        DELETE $user;
+                        COMMIT TRANSACTION;
+                    ",
+                )
+                .bind(("user", &id)))
+                .await?
+                .check()?;
             info!("The {} was deleted.", id);
         } else {
             warn!("The user has no id.");
@@ -234,16 +304,18 @@ impl ModelTrait<User> for User {
 }
 
 impl From<UserRequest> for User {
-    fn from(mut value: UserRequest) -> Self {
-        // Treat the username and password.
-        value.username = value.username.to_lowercase();
-        value.password = get_sha512(value.password.as_bytes());
+    fn from(value: UserRequest) -> Self {
+        // Treat the username and password. The username keeps its case.
+        let username = normalize(&value.username);
+        let password = hash_password(value.password.as_bytes());
 
         // Create the user.
         User {
             id: None,
-            username: value.username,
-            password: value.password,
+            username_key: username_key(&username),
+            username,
+            password,
+            token_version: 0,
             created_at: Datetime::default(),
             updated_at: Datetime::default(),
         }
@@ -2,67 +2,227 @@
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::{Datetime, Id, Thing};
 use tracing::{error, info, warn};
+use utoipa::ToSchema;
 
-use super::{media_model::Media, model_trait::ModelTrait};
+use super::{
+    media_model::{Media, MediaCursor, MediaPage, MediaQuery},
+    model_trait::ModelTrait,
+};
 use crate::database::DATABASE;
+use crate::metrics::observe_query;
+use crate::validation::normalize_genre;
+
+// Enums
+/**
+ * The role of a member in a watchlist. Each role has all the permissions of the roles before it.
+ * viewer: Can view the watchlist and its media.
+ * editor: Can also add and update the media.
+ * admin: Can also delete the media, update the watchlist and manage its members.
+*/
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[schema(
+    description = "The role of a member in a watchlist. viewer can view the watchlist and its media, editor can also add and update the media and admin can also delete the media, update the watchlist and manage its members."
+)]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+}
 
 // Structs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub user: Thing,
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberRequest {
+    pub user: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberResponse {
+    pub user: String,
+    pub role: Role,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Watchlist {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<Thing>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<Thing>,
-    pub members: Vec<Thing>,
+    pub members: Vec<Member>,
     pub title: String,
     pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<Datetime>,
     pub created_at: Datetime,
     pub updated_at: Datetime,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/**
+ * members: The members of the watchlist. If it's missing, the current members are kept.
+*/
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct WatchlistRequest {
-    pub members: Vec<String>,
+    #[serde(default)]
+    pub members: Option<Vec<MemberRequest>>,
     pub title: String,
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WatchlistTransferRequest {
+    pub new_owner: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct WatchlistResponse {
     pub id: String,
     pub owner: String,
-    pub members: Vec<String>,
+    pub members: Vec<MemberResponse>,
     pub title: String,
     pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub deleted_at: Option<Datetime>,
+    #[schema(value_type = String, format = DateTime)]
     pub created_at: Datetime,
+    #[schema(value_type = String, format = DateTime)]
     pub updated_at: Datetime,
 }
 
 // Implementations
 impl Watchlist {
     /**
-     * A method to get all the media from the watchlist.
+     * A method to get a page of media from the watchlist.
+     * The medias are sorted by the given key and then by id, so the cursor is stable.
      */
-    pub async fn get_media(&self) -> surrealdb::Result<Vec<Media>> {
+    pub async fn get_media(
+        &self,
+        query: &MediaQuery,
+        cursor: Option<&MediaCursor>,
+        limit: usize,
+    ) -> surrealdb::Result<MediaPage> {
         info!("Getting media from {}.", self.id.as_ref().unwrap());
-        match DATABASE
-            .query("SELECT * FROM media WHERE watchlist = $watchlist_id")
+
+        // Build the filters.
+        let mut filters = vec!["watchlist = $watchlist_id", "deleted_at IS NONE"];
+        if query.watched.is_some() {
+            filters.push("watched = $watched");
+        }
+        let q = query.q.as_ref().map(|q| q.trim().to_lowercase());
+        if q.as_ref().is_some_and(|q| !q.is_empty()) {
+            filters.push("string::contains(string::lowercase(title), $q)");
+        }
+        if query.kind.is_some() {
+            filters.push("kind = $kind");
+        }
+        let genre = query.genre.as_deref().map(normalize_genre);
+        if genre.as_ref().is_some_and(|genre| !genre.is_empty()) {
+            filters.push("genres CONTAINS $genre");
+        }
+        if query.year_from.is_some() {
+            filters.push("year >= $year_from");
+        }
+        if query.year_to.is_some() {
+            filters.push("year <= $year_to");
+        }
+        let filters = filters.join(" AND ");
+
+        // Start the page after the cursor.
+        let (key, cursor_value) = (query.sort.key(), query.sort.cursor_value());
+        let page_filters = match cursor {
+            None => filters.clone(),
+            Some(_) => format!(
+                "{filters} AND ({key} > {cursor_value} OR ({key} = {cursor_value} AND id > $cursor_id))"
+            ),
+        };
+
+        // Fetch one more media to know if there's a next page.
+        let mut response = observe_query("watchlist", "get_media", DATABASE
+            .query(format!(
+                "SELECT *, {key} AS sort_key FROM media WHERE {page_filters} ORDER BY sort_key ASC, id ASC LIMIT $limit"
+            ))
+            .query(format!(
+                "SELECT count() AS total FROM media WHERE {filters} GROUP ALL"
+            ))
             .bind(("watchlist_id", self.id.as_ref().unwrap()))
-            .await?
-            .take(0)
-        {
+            .bind(("watched", query.watched))
+            .bind(("q", q))
+            .bind(("kind", query.kind))
+            .bind(("genre", genre))
+            .bind(("year_from", query.year_from))
+            .bind(("year_to", query.year_to))
+            .bind(("cursor_value", cursor.map(|c| c.value.clone())))
+            .bind((
+                "cursor_id",
+                cursor.map(|c| Thing {
+                    id: Id::from(c.id.clone()),
+                    tb: String::from("media"),
+                }),
+            ))
+            .bind(("limit", limit + 1)))
+            .await?;
+
+        let mut items: Vec<Media> = match response.take(0) {
             Err(e) => {
                 error!(
                     "Couldn\'t get the media from {}.",
                     self.id.as_ref().unwrap()
                 );
-                Err(e)
+                return Err(e);
             }
-            Ok(medias) => {
-                info!("The media were successfully retrieved.");
-                Ok(medias)
+            Ok(items) => items,
+        };
+        let total: Option<usize> = response.take((1, "total"))?;
+
+        // Point the cursor to the last media of the page.
+        let next_cursor = match items.len() > limit {
+            false => None,
+            true => {
+                items.truncate(limit);
+                items
+                    .last()
+                    .map(|media| MediaCursor::after(media, query.sort).encode())
             }
-        }
+        };
+
+        info!("The media were successfully retrieved.");
+        Ok(MediaPage {
+            items,
+            next_cursor,
+            total: total.unwrap_or_default(),
+        })
+    }
+
+    /**
+     * A method to check if the watchlist is in the trash.
+     */
+    pub fn is_trashed(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /**
+     * A method to move the watchlist to the trash. Its medias are hidden until it's restored.
+     */
+    pub async fn trash(&mut self) -> surrealdb::Result<()> {
+        info!("Moving {} to the trash...", self.id.as_ref().unwrap());
+        self.deleted_at = Some(Datetime::default());
+        self.sync().await
+    }
+
+    /**
+     * A method to restore the watchlist from the trash.
+     */
+    pub async fn restore(&mut self) -> surrealdb::Result<()> {
+        info!("Restoring {} from the trash...", self.id.as_ref().unwrap());
+        self.deleted_at = None;
+        self.sync().await
     }
 
     /**
@@ -73,10 +233,43 @@ impl Watchlist {
     }
 
     /**
-     * A method to check if the watchlist has the given member.
+     * A method to get the role of the given member. The owner has no role.
+     */
+    pub fn get_role(&self, member: &Thing) -> Option<Role> {
+        self.members
+            .iter()
+            .find(|m| &m.user == member)
+            .map(|m| m.role)
+    }
+
+    /**
+     * A method to remove the given member from the watchlist. Returns if the member was found.
      */
-    pub fn has_member(&self, member: &Thing) -> bool {
-        self.members.contains(member)
+    pub fn remove_member(&mut self, member: &Thing) -> bool {
+        let members_len = self.members.len();
+        self.members.retain(|m| &m.user != member);
+        self.members.len() != members_len
+    }
+
+    /**
+     * A method to transfer the watchlist to one of its members.
+     * The new owner leaves the members and the old owner becomes an admin.
+     */
+    pub fn transfer(&mut self, new_owner: Thing) {
+        self.remove_member(&new_owner);
+        if let Some(old_owner) = self.owner.replace(new_owner) {
+            self.members.push(Member {
+                user: old_owner,
+                role: Role::Admin,
+            });
+        }
+    }
+
+    /**
+     * A method to check if the given user is the owner or a member with at least the given role.
+     */
+    pub fn has_role(&self, user: &Thing, min_role: Role) -> bool {
+        self.is_owner(user) || self.get_role(user).is_some_and(|role| role >= min_role)
     }
 
     /**
@@ -97,7 +290,13 @@ impl ModelTrait<Watchlist> for Watchlist {
 
         // Get the watchlist.
         info!("Getting {}.", &thing);
-        match DATABASE.select::<Option<Self>>(thing.clone()).await? {
+        match observe_query(
+            "watchlist",
+            "from_id",
+            DATABASE.select::<Option<Self>>(thing.clone()),
+        )
+        .await?
+        {
             None => {
                 info!("No {} found.", &thing);
                 Ok(None)
@@ -109,29 +308,6 @@ impl ModelTrait<Watchlist> for Watchlist {
         }
     }
 
-    async fn migration() -> surrealdb::Result<()> {
-        // Define the watchlist table.
-        info!("Running watchlist migration...");
-        DATABASE
-            .query(
-                "
-                    BEGIN TRANSACTION;
-                    DEFINE TABLE watchlist SCHEMAFULL;
-                    DEFINE FIELD owner ON TABLE watchlist TYPE record<user>;
-                    DEFINE FIELD members ON TABLE watchlist TYPE array;
-                    DEFINE FIELD members.* ON TABLE watchlist TYPE record<user>;
-                    DEFINE FIELD title ON TABLE watchlist TYPE string ASSERT $value = /^[a-zA-Z0-9!@#$%&*_\\-+.,<>;\\/? ]{3,20}$/;
-                    DEFINE FIELD description ON TABLE watchlist TYPE string ASSERT $value = /^[a-zA-Z0-9!@#$%&*_\\-+.,<>;\\/? ]{3,60}$/;
-                    DEFINE FIELD created_at ON TABLE watchlist TYPE datetime;
-                    DEFINE FIELD updated_at ON TABLE watchlist TYPE datetime;
-                    COMMIT TRANSACTION;
-                ",
-            )
-            .await?;
-
-        Ok(())
-    }
-
     async fn sync(&mut self) -> surrealdb::Result<()> {
         // Check if the watchlist already has an id. If not, generate a new one.
         if self.id.is_none() {
@@ -141,10 +317,14 @@ impl ModelTrait<Watchlist> for Watchlist {
         // Sync the watchlist in the database.
         self.updated_at = Datetime::default();
         info!("Syncing {} in the database...", self.id.as_ref().unwrap());
-        DATABASE
-            .update::<Option<Self>>(("watchlist", self.id.clone().unwrap()))
-            .content(&self)
-            .await?;
+        observe_query(
+            "watchlist",
+            "sync",
+            DATABASE
+                .update::<Option<Self>>(("watchlist", self.id.clone().unwrap()))
+                .content(&self),
+        )
+        .await?;
         info!("Synced {} in the database.", self.id.as_ref().unwrap());
 
         Ok(())
@@ -158,10 +338,12 @@ impl ModelTrait<Watchlist> for Watchlist {
         // Create the watchlist in the database.
         self.created_at = Datetime::default();
         self.updated_at = self.created_at.clone();
-        let created_watchlists = DATABASE
-            .create::<Vec<Self>>("watchlist")
-            .content(&self)
-            .await?;
+        let created_watchlists = observe_query(
+            "watchlist",
+            "create",
+            DATABASE.create::<Vec<Self>>("watchlist").content(&self),
+        )
+        .await?;
 
         // Check if it was really created.
         if created_watchlists.is_empty() {
@@ -182,9 +364,27 @@ impl ModelTrait<Watchlist> for Watchlist {
 
     async fn delete(self) -> surrealdb::Result<()> {
         // Check if the watchlist has an id.
+        // Its medias, their episodes and its invitations are deleted with it.
         if let Some(id) = self.id.clone() {
             info!("Deleting {}...", &id);
-            DATABASE.delete::<Option<Watchlist>>(&id).await?;
+            observe_query(
+                "watchlist",
+                "delete",
+                DATABASE
+                    .query(
+                        "
+                        BEGIN TRANSACTION;
+                        DELETE episode WHERE media.watchlist = $watchlist;
+                        DELETE media WHERE watchlist = $watchlist;
+                        DELETE invitation WHERE watchlist = $watchlist;
+                        DELETE $watchlist;
+                        COMMIT TRANSACTION;
+                    ",
+                    )
+                    .bind(("watchlist", &id)),
+            )
+            .await?
+            .check()?;
             info!("The {} was deleted.", id);
         } else {
             warn!("The watchlist has no id.");
@@ -202,14 +402,13 @@ impl From<WatchlistRequest> for Watchlist {
             owner: None,
             members: value
                 .members
-                .iter()
-                .map(|member| Thing {
-                    id: Id::from(member),
-                    tb: String::from("user"),
-                })
+                .unwrap_or_default()
+                .into_iter()
+                .map(Member::from)
                 .collect(),
             title: value.title,
             description: value.description,
+            deleted_at: None,
             created_at: Datetime::default(),
             updated_at: Datetime::default(),
         }
@@ -223,13 +422,35 @@ impl From<Watchlist> for WatchlistResponse {
             owner: value.owner.expect("Logic error").id.to_string(),
             members: value
                 .members
-                .iter()
-                .map(|member| member.id.to_string())
+                .into_iter()
+                .map(MemberResponse::from)
                 .collect(),
             title: value.title,
             description: value.description,
+            deleted_at: value.deleted_at,
             created_at: value.created_at,
             updated_at: value.updated_at,
         }
     }
 }
+
+impl From<MemberRequest> for Member {
+    fn from(value: MemberRequest) -> Self {
+        Self {
+            user: Thing {
+                id: Id::from(value.user),
+                tb: String::from("user"),
+            },
+            role: value.role,
+        }
+    }
+}
+
+impl From<Member> for MemberResponse {
+    fn from(value: Member) -> Self {
+        Self {
+            user: value.user.id.to_string(),
+            role: value.role,
+        }
+    }
+}
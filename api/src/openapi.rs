@@ -0,0 +1,217 @@
+// Libs
+use once_cell::sync::Lazy;
+use utoipa::{
+    openapi::{
+        header::HeaderBuilder,
+        path::Operation,
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+        ContentBuilder, Object, Ref, RefOr, Response, ResponseBuilder, Type,
+    },
+    Modify, OpenApi,
+};
+
+use crate::controllers::{
+    episode_controler, health_controler, invitation_controler, media_controler, metrics_controler,
+    openapi_controler, search_controler, trash_controler, user_controler, watchlist_controler,
+};
+use crate::models::media_model::MediaSort;
+
+// Data
+/**
+ * The spec as JSON. It's generated from the annotations of the handlers, so it's serialized only once.
+*/
+pub static OPENAPI_JSON: Lazy<String> = Lazy::new(|| {
+    ApiDoc::openapi()
+        .to_json()
+        .expect("Couldn\'t serialize the OpenAPI spec.")
+});
+
+// Structs
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Watchlist API",
+        description = "An api to manipulate data from database.",
+        contact(name = "Gustavo Saiki", email = "GSaiki26@gmail.com"),
+        license(name = "MIT")
+    ),
+    paths(
+        episode_controler::delete_episode,
+        episode_controler::get_episodes,
+        episode_controler::post_episode,
+        episode_controler::post_episode_watched,
+        episode_controler::post_season_watched,
+        health_controler::get_healthz,
+        health_controler::get_readyz,
+        health_controler::get_version,
+        invitation_controler::get_invitations,
+        invitation_controler::post_invitation_accept,
+        invitation_controler::post_invitation_decline,
+        invitation_controler::post_watchlist_invitation,
+        media_controler::delete_media,
+        media_controler::get_media,
+        media_controler::patch_media,
+        media_controler::post_media,
+        metrics_controler::get_metrics,
+        openapi_controler::get_openapi,
+        search_controler::get_search,
+        trash_controler::get_trash,
+        trash_controler::post_trash_restore,
+        user_controler::delete_user,
+        user_controler::get_user,
+        user_controler::patch_user,
+        user_controler::post_user,
+        user_controler::post_user_login,
+        user_controler::post_user_refresh,
+        watchlist_controler::delete_watchlist,
+        watchlist_controler::get_watchlist,
+        watchlist_controler::get_watchlist_medias,
+        watchlist_controler::get_watchlists,
+        watchlist_controler::patch_watchlist,
+        watchlist_controler::post_watchlist,
+        watchlist_controler::post_watchlist_leave,
+        watchlist_controler::post_watchlist_transfer,
+    ),
+    // The query parameters only reference their schemas.
+    components(schemas(MediaSort)),
+    modifiers(&SecuritySchemes, &LayerResponses),
+    security(("bearer_auth" = [])),
+    tags(
+        (name = "episode", description = "The episodes of the series and animes, and their progress."),
+        (name = "invitation", description = "The invitations to join some watchlist."),
+        (name = "media", description = "The medias of the watchlists."),
+        (name = "probes", description = "The probes of the orchestrator. No auth nor Accept header is needed."),
+        (name = "search", description = "The full-text search in the watchlists and medias."),
+        (name = "trash", description = "The trashed watchlists and medias."),
+        (name = "user", description = "The users and their tokens."),
+        (name = "watchlist", description = "The watchlists and their members."),
+    )
+)]
+pub struct ApiDoc;
+
+/**
+ * Adds the Basic authorization of the login and the Bearer tokens of the other routes.
+*/
+struct SecuritySchemes;
+
+/**
+ * Adds the responses of the middlewares to the operations they wrap. The probes skip the middlewares.
+ * The responses declared by the handlers are kept.
+*/
+struct LayerResponses;
+
+// Implementations
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "basic_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Basic).build()),
+        );
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+impl Modify for LayerResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.post,
+                &mut item.patch,
+                &mut item.delete,
+                &mut item.put,
+            ];
+            for operation in operations.into_iter().flatten() {
+                if is_probe(operation) {
+                    continue;
+                }
+
+                let responses = &mut operation.responses.responses;
+                if needs_auth(operation.security.as_deref()) {
+                    responses.entry(String::from("401")).or_insert_with(|| {
+                        error_response("The authorization is missing, invalid or expired.", false)
+                    });
+                }
+                responses.insert(
+                    String::from("406"),
+                    RefOr::T(
+                        ResponseBuilder::new()
+                            .description("The Accept header doesn\'t allow application/json.")
+                            .build(),
+                    ),
+                );
+                responses.entry(String::from("429")).or_insert_with(|| {
+                    error_response(
+                        "Too many requests or failed logins. The rate limit is enabled.",
+                        true,
+                    )
+                });
+                responses.insert(
+                    String::from("500"),
+                    error_response("Something went wrong with the database.", false),
+                );
+                responses.insert(
+                    String::from("503"),
+                    error_response("The connection to the database is degraded.", true),
+                );
+            }
+        }
+    }
+}
+
+// Functions
+/**
+ * A method to check if the operation is some probe.
+*/
+fn is_probe(operation: &Operation) -> bool {
+    operation
+        .tags
+        .as_ref()
+        .is_some_and(|tags| tags.iter().any(|tag| tag == "probes"))
+}
+
+/**
+ * A method to check if the operation needs some authorization.
+ * Without its own requirements, the operation uses the Bearer token of the spec.
+*/
+fn needs_auth(security: Option<&[utoipa::openapi::security::SecurityRequirement]>) -> bool {
+    match security {
+        None => true,
+        Some(requirements) => requirements
+            .iter()
+            .any(|requirement| requirement != &Default::default()),
+    }
+}
+
+/**
+ * A method to create some response with the failed ResponseBody.
+ * retry_after: If the response has the Retry-After header.
+*/
+fn error_response(description: &str, retry_after: bool) -> RefOr<Response> {
+    let mut response = ResponseBuilder::new().description(description).content(
+        "application/json",
+        ContentBuilder::new()
+            .schema(Some(Ref::from_schema_name("ResponseBody")))
+            .build(),
+    );
+    if retry_after {
+        response = response.header(
+            "Retry-After",
+            HeaderBuilder::new()
+                .schema(Object::with_type(Type::Integer))
+                .description(Some("The seconds to wait before retrying."))
+                .build(),
+        );
+    }
+
+    RefOr::T(response.build())
+}
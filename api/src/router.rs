@@ -1,5 +1,3 @@
-use std::time::Duration;
-
 // Libs
 use axum::{
     middleware,
@@ -9,31 +7,94 @@ use axum::{
 use tower::ServiceBuilder;
 use tower_http::timeout::TimeoutLayer;
 
+use crate::config::config;
+use crate::metrics::track_metrics;
 use crate::{
-    controllers::media_controler::*, controllers::user_controler::*,
-    controllers::watchlist_controler::*, middlewares::acceptable_middleware::acceptable_headers,
-    middlewares::log_middleware::log_stream,
+    controllers::episode_controler::*, controllers::health_controler::*,
+    controllers::invitation_controler::*, controllers::media_controler::*,
+    controllers::metrics_controler::*, controllers::openapi_controler::*,
+    controllers::search_controler::*, controllers::trash_controler::*,
+    controllers::user_controler::*, controllers::watchlist_controler::*,
+    middlewares::acceptable_middleware::acceptable_headers,
+    middlewares::database_middleware::database_available, middlewares::log_middleware::log_stream,
+    middlewares::rate_limit_middleware::rate_limit,
+    middlewares::request_id_middleware::request_span,
 };
 
 // Functions
 pub fn get_router() -> Router {
-    Router::new()
+    let router = Router::new()
+        .route("/invitation", get(get_invitations))
+        .route(
+            "/invitation/:invitation_id/accept",
+            post(post_invitation_accept),
+        )
+        .route(
+            "/invitation/:invitation_id/decline",
+            post(post_invitation_decline),
+        )
         .route("/media", post(post_media))
         .route("/media/:media_id", patch(patch_media))
         .route("/media/:media_id", delete(delete_media))
         .route("/media/:media_id", get(get_media))
+        .route("/media/:media_id/episode", get(get_episodes))
+        .route("/media/:media_id/episode", post(post_episode))
+        .route(
+            "/media/:media_id/episode/:episode_id",
+            delete(delete_episode),
+        )
+        .route(
+            "/media/:media_id/episode/:episode_id/watched",
+            post(post_episode_watched),
+        )
+        .route(
+            "/media/:media_id/season/:season/watched",
+            post(post_season_watched),
+        )
+        .route("/search", get(get_search))
+        .route("/trash", get(get_trash))
+        .route("/trash/:trash_id/restore", post(post_trash_restore))
         .route("/user", post(post_user))
         .route("/user", patch(patch_user))
         .route("/user", delete(delete_user))
         .route("/user/:user_id", get(get_user))
         .route("/user/login", post(post_user_login))
+        .route("/user/refresh", post(post_user_refresh))
         .route("/watchlist", post(post_watchlist))
         .route("/watchlist", get(get_watchlists))
         .route("/watchlist/:watchlist_id", get(get_watchlist))
         .route("/watchlist/:watchlist_id", patch(patch_watchlist))
         .route("/watchlist/:watchlist_id", delete(delete_watchlist))
+        .route(
+            "/watchlist/:watchlist_id/invitation",
+            post(post_watchlist_invitation),
+        )
+        .route("/watchlist/:watchlist_id/leave", post(post_watchlist_leave))
         .route("/watchlist/:watchlist_id/media", get(get_watchlist_medias))
+        .route(
+            "/watchlist/:watchlist_id/transfer",
+            post(post_watchlist_transfer),
+        )
+        .layer(middleware::from_fn(database_available))
+        .layer(middleware::from_fn(rate_limit))
         .layer(middleware::from_fn(log_stream))
         .layer(middleware::from_fn(acceptable_headers))
-        .layer(ServiceBuilder::new().layer(TimeoutLayer::new(Duration::from_secs(10))))
+        // The probes are added after the layers, so they skip the checks of the other routes.
+        .route("/healthz", get(get_healthz))
+        .route("/metrics", get(get_metrics))
+        .route("/openapi.json", get(get_openapi))
+        .route("/readyz", get(get_readyz))
+        .route("/version", get(get_version));
+
+    // The docs UI reads the spec from GET /openapi.json.
+    #[cfg(feature = "docs-ui")]
+    let router = router.merge(
+        utoipa_swagger_ui::SwaggerUi::new("/docs")
+            .config(utoipa_swagger_ui::Config::from("/openapi.json")),
+    );
+
+    router
+        .layer(ServiceBuilder::new().layer(TimeoutLayer::new(config().server.request_timeout)))
+        .layer(middleware::from_fn(track_metrics))
+        .layer(middleware::from_fn(request_span))
 }
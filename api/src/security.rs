@@ -1,34 +1,253 @@
 // Libs
-use ring::digest::{digest, SHA512};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use once_cell::sync::{Lazy, OnceCell};
+use ring::{
+    constant_time::verify_slices_are_equal,
+    digest::{digest, SHA512},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
+use utoipa::ToSchema;
+
+// Data
+static TOKEN_KEYS: OnceCell<(EncodingKey, DecodingKey)> = OnceCell::new();
+static TOKEN_SECRET_MIN_LENGTH: usize = 32;
+pub static ACCESS_TOKEN_TTL: u64 = 15 * 60;
+pub static REFRESH_TOKEN_TTL: u64 = 7 * 24 * 60 * 60;
+/**
+ * A hash verified when the user doesn't exist, so the failed logins take the same time.
+*/
+static DUMMY_HASH: Lazy<String> = Lazy::new(|| hash_password(b"dummy-password"));
+
+// Enums
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    Access,
+    Refresh,
+}
+
+// Structs
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_secret: Option<String>,
+}
+
+/**
+ * ver: The token version of the user when the token was signed. The tokens with an older version are revoked.
+*/
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Claims {
+    pub sub: String,
+    #[serde(default)]
+    pub ver: u32,
+    pub kind: TokenKind,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+}
+
+// Implementations
+impl SecurityConfig {
+    /**
+     * A method to check if the token secret is defined and long enough.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.token_secret {
+            None => Err(String::from("The security.token_secret must be defined.")),
+            Some(secret) if secret.len() < TOKEN_SECRET_MIN_LENGTH => Err(format!(
+                "The security.token_secret must have at least {} characters.",
+                TOKEN_SECRET_MIN_LENGTH
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+}
 
 // Functions
 /**
- * A method to get the SHA512 from some data.
+ * A method to initialize the keys used to sign the tokens.
 */
-pub fn get_sha512(data: &[u8]) -> String {
-    // Encrypt the password to SHA512.
-    info!("Encrypting some field...");
-    let pass = digest(&SHA512, data);
-    hex::encode(pass)
+pub fn initialize_security(config: &SecurityConfig) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Initializing the token keys...");
+    config.validate()?;
+    let secret = config.token_secret.as_deref().unwrap_or_default();
+
+    let keys = (
+        EncodingKey::from_secret(secret.as_bytes()),
+        DecodingKey::from_secret(secret.as_bytes()),
+    );
+    if TOKEN_KEYS.set(keys).is_err() {
+        warn!("The token keys were already initialized.");
+    }
+
+    info!("Successfully initialized the token keys.");
+    Ok(())
+}
+
+/**
+ * A method to generate a new signed token to the given user id and token version.
+*/
+pub fn generate_token(user_id: &str, token_version: u32, kind: TokenKind) -> String {
+    let (encoding_key, _) = TOKEN_KEYS
+        .get()
+        .expect("The token keys weren't initialized.");
+    let iat = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Logic error.")
+        .as_secs();
+    let ttl = match kind {
+        TokenKind::Access => ACCESS_TOKEN_TTL,
+        TokenKind::Refresh => REFRESH_TOKEN_TTL,
+    };
+
+    let claims = Claims {
+        sub: String::from(user_id),
+        ver: token_version,
+        kind,
+        iat,
+        exp: iat + ttl,
+    };
+    encode(&Header::default(), &claims, encoding_key).expect("Couldn\'t sign the token.")
 }
 
 /**
- * A method to check if some field is valid.
+ * A method to generate a new access and refresh token to the given user id and token version.
 */
-pub fn is_valid_field(field: &str, max_length: u8) -> bool {
-    // Check if the username is valid.
-    info!("Checking if the field is valid...");
-    let expression = format!("^[a-zA-Z0-9!@#$%&*_\\-+.,<>;\\/? ]{{3,{max_length}}}$");
-    let re = regex::Regex::new(&expression).unwrap();
-    match re.is_match(field) {
-        true => {
-            info!("The field is valid.");
-            true
+pub fn generate_token_pair(user_id: &str, token_version: u32) -> TokenPair {
+    info!("Generating a new token pair...");
+    TokenPair {
+        access_token: generate_token(user_id, token_version, TokenKind::Access),
+        refresh_token: generate_token(user_id, token_version, TokenKind::Refresh),
+        token_type: String::from("Bearer"),
+        expires_in: ACCESS_TOKEN_TTL,
+    }
+}
+
+/**
+ * A method to validate some token. Returns its claims if the token is valid and has the expected kind.
+*/
+pub fn validate_token(token: &str, kind: TokenKind) -> Option<Claims> {
+    let (_, decoding_key) = TOKEN_KEYS
+        .get()
+        .expect("The token keys weren't initialized.");
+    match decode::<Claims>(token, decoding_key, &Validation::default()) {
+        Err(e) => {
+            warn!("The token is invalid. {}", e);
+            None
+        }
+        Ok(data) if data.claims.kind != kind => {
+            warn!("The token has the wrong kind.");
+            None
         }
-        false => {
-            warn!("The field is invalid.");
+        Ok(data) => Some(data.claims),
+    }
+}
+
+/**
+ * A method to hash some password using Argon2id. The hash is returned in the PHC string format.
+*/
+pub fn hash_password(password: &[u8]) -> String {
+    // Generate a new random salt.
+    info!("Hashing some password...");
+    let mut salt = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .expect("Couldn\'t generate the salt.");
+    let salt = SaltString::encode_b64(&salt).expect("Logic error.");
+
+    Argon2::default()
+        .hash_password(password, &salt)
+        .expect("Couldn\'t hash the password.")
+        .to_string()
+}
+
+/**
+ * A method to check if some password matches the stored hash.
+ * Legacy unsalted SHA512 hashes are also accepted.
+*/
+pub fn verify_password(password: &[u8], hash: &str) -> bool {
+    if is_legacy_hash(hash) {
+        info!("Verifying some password against a legacy hash...");
+        // The comparison takes the same time wherever the hashes differ.
+        return verify_slices_are_equal(get_sha512(password).as_bytes(), hash.as_bytes()).is_ok();
+    }
+
+    match PasswordHash::new(hash) {
+        Err(e) => {
+            warn!("The stored hash is invalid. {}", e);
             false
         }
+        Ok(parsed_hash) => Argon2::default()
+            .verify_password(password, &parsed_hash)
+            .is_ok(),
     }
 }
+
+/**
+ * A method to spend the time of a password verification when there's no user to check.
+ * Otherwise, the time of the failed logins would tell whether the username exists.
+*/
+pub fn verify_dummy_password(password: &[u8]) {
+    verify_password(password, &DUMMY_HASH);
+}
+
+/**
+ * A method to check if some stored hash should be replaced by a new Argon2id hash.
+*/
+pub fn needs_rehash(hash: &str) -> bool {
+    if is_legacy_hash(hash) {
+        return true;
+    }
+
+    // Check if the hash was made with the current algorithm and parameters.
+    match PasswordHash::new(hash) {
+        Err(_) => true,
+        Ok(parsed_hash) => {
+            let current = Argon2::default().params().clone();
+            match argon2::Params::try_from(&parsed_hash) {
+                Err(_) => true,
+                Ok(params) => {
+                    parsed_hash.algorithm != argon2::Algorithm::Argon2id.ident()
+                        || params.m_cost() != current.m_cost()
+                        || params.t_cost() != current.t_cost()
+                        || params.p_cost() != current.p_cost()
+                }
+            }
+        }
+    }
+}
+
+/**
+ * A method to check if some hash is a legacy unsalted SHA512 hex.
+*/
+fn is_legacy_hash(hash: &str) -> bool {
+    hash.len() == 128 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/**
+ * A method to get the SHA512 from some data.
+ * Only used to check legacy passwords. Use hash_password() to store new ones.
+*/
+fn get_sha512(data: &[u8]) -> String {
+    // Encrypt the password to SHA512.
+    info!("Encrypting some field...");
+    let pass = digest(&SHA512, data);
+    hex::encode(pass)
+}
@@ -0,0 +1,76 @@
+// Libs
+use std::{
+    future::{Future, IntoFuture},
+    net::SocketAddr,
+    time::Duration,
+};
+
+use axum::Router;
+use tokio::{net::TcpListener, sync::watch};
+use tracing::{info, warn};
+
+// Functions
+/**
+ * A method to wait for the SIGINT or SIGTERM signal.
+*/
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Couldn\'t listen to the SIGINT signal.");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Couldn\'t listen to the SIGTERM signal.")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received SIGINT."),
+        _ = terminate => info!("Received SIGTERM."),
+    }
+}
+
+/**
+ * A method to serve the router until the signal is received, then drain the connections.
+ * The connections still open after the deadline are closed.
+*/
+pub async fn serve_with_shutdown(
+    listener: TcpListener,
+    router: Router,
+    signal: impl Future<Output = ()> + Send + 'static,
+    deadline: Duration,
+) -> Result<(), std::io::Error> {
+    let (sender, mut receiver) = watch::channel(false);
+    let router = router.into_make_service_with_connect_info::<SocketAddr>();
+    let server = axum::serve(listener, router).with_graceful_shutdown(async move {
+        signal.await;
+        info!("Shutting down. Draining the connections...");
+        let _ = sender.send(true);
+    });
+
+    // The deadline only starts once the signal is received.
+    let drain_deadline = async {
+        let _ = receiver.wait_for(|stopping| *stopping).await;
+        tokio::time::sleep(deadline).await;
+    };
+
+    tokio::select! {
+        res = server.into_future() => {
+            info!("All the connections were drained.");
+            res
+        }
+        _ = drain_deadline => {
+            warn!(
+                "Couldn\'t drain the connections in {}s. Closing them.",
+                deadline.as_secs()
+            );
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Libs
+use axum::http::HeaderMap;
+use opentelemetry::{global, propagation::Extractor, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{self, Sampler},
+    Resource,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{Span, Subscriber};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+// Structs
+/**
+ * The export of the request spans to some OTLP collector. Disabled while the endpoint isn't defined.
+*/
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+    pub sample_ratio: f64,
+}
+
+/**
+ * A wrapper to read the W3C trace context from the request headers.
+*/
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+// Implementations
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: String::from("watchlist-api"),
+            sample_ratio: 1.0,
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /**
+     * A method to check if the configuration values are coherent.
+     * The traces are sent over HTTP, so the endpoint must be some http:// URI.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(endpoint) = &self.otlp_endpoint {
+            if !endpoint.starts_with("http://") {
+                return Err(String::from(
+                    "The telemetry.otlp_endpoint must be an http:// URI.",
+                ));
+            }
+        }
+        if self.service_name.is_empty() {
+            return Err(String::from("The telemetry.service_name can\'t be empty."));
+        }
+        if !(0.0..=1.0).contains(&self.sample_ratio) {
+            return Err(String::from(
+                "The telemetry.sample_ratio must be between 0 and 1.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+// Functions
+/**
+ * A method to create the layer that exports the spans to the collector.
+ * Returns None if the export is disabled. Must be called inside the tokio runtime.
+*/
+pub fn telemetry_layer<S>(config: &TelemetryConfig) -> Result<Option<impl Layer<S>>, String>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    config.validate()?;
+    let endpoint = match &config.otlp_endpoint {
+        None => return Ok(None),
+        Some(endpoint) => endpoint,
+    };
+
+    // The parents of the sampled requests keep deciding for their children.
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sample_ratio)));
+    let resource = Resource::new(vec![KeyValue::new(
+        "service.name",
+        config.service_name.clone(),
+    )]);
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config()
+                .with_sampler(sampler)
+                .with_resource(resource),
+        )
+        .install_batch(runtime::Tokio)
+        .map_err(|e| format!("Couldn\'t create the OTLP exporter. {}", e))?;
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+/**
+ * A method to continue the trace of the client, when the request has a traceparent header.
+*/
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    let context =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    span.set_parent(context);
+}
+
+/**
+ * A method to export the spans still buffered. Blocks until the collector answers.
+*/
+pub fn shutdown_telemetry() {
+    global::shutdown_tracer_provider();
+}
@@ -0,0 +1,73 @@
+// Libs
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Datetime;
+use tracing::{error, info};
+
+use crate::config::duration_secs;
+use crate::models::trash_model::Trash;
+
+// Structs
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrashConfig {
+    pub retention_days: u32,
+    #[serde(rename = "purge_interval_secs", with = "duration_secs")]
+    pub purge_interval: Duration,
+}
+
+// Implementations
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            purge_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl TrashConfig {
+    /**
+     * A method to check if the configuration values are coherent.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if self.retention_days == 0 {
+            return Err(String::from(
+                "The trash.retention_days must be greater than 0.",
+            ));
+        }
+        if self.purge_interval.is_zero() {
+            return Err(String::from(
+                "The trash.purge_interval_secs must be greater than 0.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// Functions
+/**
+ * A method to start the task that purges the trash. Everything trashed longer than the retention is deleted.
+*/
+pub fn initialize_trash_purge(config: TrashConfig) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Initializing the trash purge...");
+    config.validate()?;
+    let retention = chrono::Duration::days(config.retention_days.into());
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.purge_interval);
+        loop {
+            interval.tick().await;
+            let before = Datetime::from(Utc::now() - retention);
+            if let Err(e) = Trash::purge(before).await {
+                error!("Couldn\'t purge the trash. {}", e);
+            }
+        }
+    });
+
+    info!("Successfully initialized the trash purge.");
+    Ok(())
+}
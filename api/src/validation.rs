@@ -0,0 +1,506 @@
+// Libs
+use std::collections::HashMap;
+
+use caseless::default_case_fold_str;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::warn;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::config::config;
+use crate::controllers::api_error::FieldError;
+use crate::models::{
+    episode_model::{EpisodeRequest, WatchedRequest},
+    invitation_model::InvitationRequest,
+    media_model::{MediaQuery, MediaRequest, MEDIA_PAGE_MAX_LIMIT},
+    search_model::{SearchQuery, SEARCH_MAX_LIMIT, SEARCH_MAX_QUERY_LENGTH},
+    user_model::UserRequest,
+    watchlist_model::{WatchlistRequest, WatchlistTransferRequest},
+};
+
+// Data
+/**
+ * The rules of the text fields. They're the only definition of the limits:
+ * the requests, the configured limits and the migrations are built from them.
+ * The lengths are in graphemes, so "Amélie" has 6 whatever its normalization.
+*/
+pub static USERNAME: FieldRule = FieldRule {
+    name: "username",
+    charset: TEXT_CHARSET,
+    min_length: 3,
+    max_length: 20,
+};
+pub static TITLE: FieldRule = FieldRule {
+    name: "title",
+    charset: TEXT_CHARSET,
+    min_length: 3,
+    max_length: 20,
+};
+pub static DESCRIPTION: FieldRule = FieldRule {
+    name: "description",
+    charset: TEXT_CHARSET,
+    min_length: 3,
+    max_length: 60,
+};
+pub static GENRE: FieldRule = FieldRule {
+    name: "genre",
+    charset: TEXT_CHARSET,
+    min_length: 2,
+    max_length: 30,
+};
+pub static FIELD_RULES: &[&FieldRule] = &[&USERNAME, &TITLE, &DESCRIPTION, &GENRE];
+
+/**
+ * The rules of the number fields of the medias and their episodes. The runtime is in minutes.
+ * The external ids are the numeric ids of the TMDB and the AniList. The season 0 has the specials.
+*/
+pub static YEAR: RangeRule = RangeRule {
+    name: "year",
+    min: 1870,
+    max: 2100,
+};
+pub static RUNTIME: RangeRule = RangeRule {
+    name: "runtime",
+    min: 1,
+    max: 10_000,
+};
+pub static EXTERNAL_ID: RangeRule = RangeRule {
+    name: "external_id",
+    min: 1,
+    max: i32::MAX as u32,
+};
+pub static SEASON: RangeRule = RangeRule {
+    name: "season",
+    min: 0,
+    max: 100,
+};
+pub static EPISODE_NUMBER: RangeRule = RangeRule {
+    name: "episode_number",
+    min: 1,
+    max: 10_000,
+};
+pub static RANGE_RULES: &[&RangeRule] = &[&YEAR, &RUNTIME, &EXTERNAL_ID, &SEASON, &EPISODE_NUMBER];
+
+/**
+ * The genres of a media. They're stored lowercase, so the filters don't depend on the case.
+*/
+pub static MAX_GENRES: usize = 10;
+
+/**
+ * The ids of the IMDb, like "tt0133093".
+*/
+pub static IMDB_ID_PATTERN: &str = "^tt[0-9]{7,10}$";
+static IMDB_ID_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(IMDB_ID_PATTERN).expect("The IMDb id pattern is invalid."));
+
+/**
+ * The Unicode letters, marks and numbers, the space and some punctuation.
+*/
+static TEXT_CHARSET: &str = r"\p{L}\p{M}\p{N}!@#$%&*_\-+.,<>;\/? ";
+
+/**
+ * The regexes of the text fields by their name. They're compiled once, at their first check.
+*/
+static FIELD_REGEXES: Lazy<HashMap<&str, Regex>> = Lazy::new(|| {
+    FIELD_RULES
+        .iter()
+        .map(|rule| {
+            let re = Regex::new(&rule.pattern()).expect("The pattern of the rule is invalid.");
+            (rule.name, re)
+        })
+        .collect()
+});
+
+/**
+ * The database can't count the graphemes, so it only limits the characters.
+ * Each grapheme can have up to this many characters, so the combining marks can't be abused.
+*/
+static MAX_CHARS_PER_GRAPHEME: usize = 4;
+
+/**
+ * The lengths of the passwords. Only the hash is stored, so they aren't in the schema.
+*/
+pub static PASSWORD_MIN_LENGTH: usize = 8;
+pub static PASSWORD_MAX_LENGTH: usize = 128;
+
+// Structs
+/**
+ * The characters and the lengths allowed in some text field.
+ * name: The name of the field in the requests, like "title".
+ * Every rule must be in the FIELD_RULES, where their regexes are compiled.
+*/
+#[derive(Debug)]
+pub struct FieldRule {
+    pub name: &'static str,
+    charset: &'static str,
+    pub min_length: u8,
+    pub max_length: u8,
+}
+
+/**
+ * The inclusive bounds of some number field.
+ * name: The name of the rule, like "year". The fields are named by the requests.
+*/
+#[derive(Debug)]
+pub struct RangeRule {
+    pub name: &'static str,
+    pub min: u32,
+    pub max: u32,
+}
+
+// Traits
+pub trait Validate {
+    /**
+     * A method to normalize the text fields of the request. Called before the validation.
+     */
+    fn normalize(&mut self) {}
+
+    /**
+     * A method to get every invalid field of the request. Empty if it's valid.
+     */
+    fn validate(&self) -> Vec<FieldError>;
+}
+
+// Implementations
+impl FieldRule {
+    /**
+     * A method to get the regex of the characters of the field.
+     * The lengths aren't in the regex, as repeating the Unicode classes would be too big.
+     */
+    fn pattern(&self) -> String {
+        format!("^[{}]+$", self.charset)
+    }
+
+    /**
+     * A method to get the SurrealQL condition of the field, used after the `ASSERT $value =` of the migrations.
+     */
+    pub fn surql(&self) -> String {
+        format!(
+            "/{}/ AND string::len($value) >= {} AND string::len($value) <= {}",
+            self.pattern(),
+            self.min_length,
+            usize::from(self.max_length) * MAX_CHARS_PER_GRAPHEME
+        )
+    }
+
+    /**
+     * A method to check if the value is valid, up to the configured max length.
+     * The value must be normalized before.
+     */
+    pub fn is_valid(&self, value: &str, max_length: u8) -> bool {
+        let re = &FIELD_REGEXES[self.name];
+        let graphemes = value.graphemes(true).count();
+        let chars = value.chars().count();
+        re.is_match(value)
+            && (usize::from(self.min_length)..=usize::from(max_length)).contains(&graphemes)
+            && chars <= usize::from(max_length) * MAX_CHARS_PER_GRAPHEME
+    }
+
+    /**
+     * A method to add the error of the field if the value isn\'t valid.
+     */
+    pub fn check(&self, errors: &mut Vec<FieldError>, value: &str, max_length: u8) {
+        self.check_field(errors, self.name, value, max_length);
+    }
+
+    /**
+     * A method to add the error of some field following the rule, like `genres[0]`, if the value isn't valid.
+     */
+    pub fn check_field(
+        &self,
+        errors: &mut Vec<FieldError>,
+        field: &str,
+        value: &str,
+        max_length: u8,
+    ) {
+        if !self.is_valid(value, max_length) {
+            warn!("The {} is invalid.", field);
+            errors.push(FieldError::new(
+                field,
+                &format!(
+                    "The {} must have between {} and {} letters, numbers, spaces or !@#$%&*_-+.,<>;/? characters.",
+                    self.name, self.min_length, max_length
+                ),
+            ));
+        }
+    }
+}
+
+impl RangeRule {
+    /**
+     * A method to get the SurrealQL condition of the field, used after the `ASSERT $value = NONE OR` of the migrations.
+     */
+    pub fn surql(&self) -> String {
+        format!("$value >= {} AND $value <= {}", self.min, self.max)
+    }
+
+    /**
+     * A method to add the error of the field if the value is out of the bounds.
+     */
+    pub fn check(&self, errors: &mut Vec<FieldError>, field: &str, value: Option<u32>) {
+        if value.is_some_and(|value| !(self.min..=self.max).contains(&value)) {
+            warn!("The {} is invalid.", field);
+            errors.push(FieldError::new(
+                field,
+                &format!(
+                    "The {} must be between {} and {}.",
+                    field, self.min, self.max
+                ),
+            ));
+        }
+    }
+}
+
+impl Validate for UserRequest {
+    fn normalize(&mut self) {
+        self.username = normalize(&self.username);
+    }
+
+    /**
+     * The username follows the rule. The configured limit is only checked for the new usernames, by the handlers.
+     */
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        USERNAME.check(&mut errors, &self.username, USERNAME.max_length);
+
+        let password_length = self.password.chars().count();
+        if !(PASSWORD_MIN_LENGTH..=PASSWORD_MAX_LENGTH).contains(&password_length) {
+            warn!("The password is invalid.");
+            errors.push(FieldError::new(
+                "password",
+                &format!(
+                    "The password must have between {} and {} characters.",
+                    PASSWORD_MIN_LENGTH, PASSWORD_MAX_LENGTH
+                ),
+            ));
+        }
+
+        errors
+    }
+}
+
+impl Validate for WatchlistRequest {
+    fn normalize(&mut self) {
+        self.title = normalize(&self.title);
+        self.description = normalize(&self.description);
+    }
+
+    fn validate(&self) -> Vec<FieldError> {
+        let limits = &config().limits;
+        let mut errors = Vec::new();
+        TITLE.check(&mut errors, &self.title, limits.title_max_length);
+        DESCRIPTION.check(
+            &mut errors,
+            &self.description,
+            limits.description_max_length,
+        );
+        for (i, member) in self.members.iter().flatten().enumerate() {
+            if member.user.is_empty() {
+                errors.push(FieldError::new(
+                    &format!("members[{}].user", i),
+                    "The user of the member is required.",
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+impl Validate for MediaRequest {
+    fn normalize(&mut self) {
+        self.title = normalize(&self.title);
+        self.description = normalize(&self.description);
+
+        // Keep the first of the genres that only differ by their case.
+        let mut genres: Vec<String> = Vec::new();
+        for genre in &self.genres {
+            let genre = normalize_genre(genre);
+            if !genres.contains(&genre) {
+                genres.push(genre);
+            }
+        }
+        self.genres = genres;
+    }
+
+    fn validate(&self) -> Vec<FieldError> {
+        let limits = &config().limits;
+        let mut errors = Vec::new();
+        TITLE.check(&mut errors, &self.title, limits.title_max_length);
+        DESCRIPTION.check(
+            &mut errors,
+            &self.description,
+            limits.description_max_length,
+        );
+        if self.watchlist.is_empty() {
+            errors.push(FieldError::new("watchlist", "The watchlist is required."));
+        }
+
+        YEAR.check(&mut errors, "year", self.year.map(u32::from));
+        RUNTIME.check(&mut errors, "runtime", self.runtime.map(u32::from));
+        if self.genres.len() > MAX_GENRES {
+            errors.push(FieldError::new(
+                "genres",
+                &format!("The media can have up to {} genres.", MAX_GENRES),
+            ));
+        }
+        for (i, genre) in self.genres.iter().enumerate() {
+            GENRE.check_field(
+                &mut errors,
+                &format!("genres[{}]", i),
+                genre,
+                GENRE.max_length,
+            );
+        }
+
+        let ids = &self.external_ids;
+        if ids
+            .imdb
+            .as_ref()
+            .is_some_and(|imdb| !IMDB_ID_REGEX.is_match(imdb))
+        {
+            errors.push(FieldError::new(
+                "external_ids.imdb",
+                "The external_ids.imdb must be like tt0133093.",
+            ));
+        }
+        EXTERNAL_ID.check(&mut errors, "external_ids.tmdb", ids.tmdb);
+        EXTERNAL_ID.check(&mut errors, "external_ids.anilist", ids.anilist);
+
+        errors
+    }
+}
+
+impl Validate for EpisodeRequest {
+    fn normalize(&mut self) {
+        self.title = self.title.as_deref().map(normalize);
+    }
+
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        SEASON.check(&mut errors, "season", Some(u32::from(self.season)));
+        EPISODE_NUMBER.check(&mut errors, "number", Some(u32::from(self.number)));
+        if let Some(title) = &self.title {
+            TITLE.check(&mut errors, title, config().limits.title_max_length);
+        }
+
+        errors
+    }
+}
+
+impl Validate for WatchedRequest {
+    /**
+     * The watched is a boolean, so any parsed body is valid.
+     */
+    fn validate(&self) -> Vec<FieldError> {
+        Vec::new()
+    }
+}
+
+impl Validate for InvitationRequest {
+    fn normalize(&mut self) {
+        self.username = normalize(&self.username);
+    }
+
+    /**
+     * The username of some existing user, so it follows the rule instead of the configured limit.
+     */
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        USERNAME.check(&mut errors, &self.username, USERNAME.max_length);
+
+        errors
+    }
+}
+
+impl Validate for WatchlistTransferRequest {
+    /**
+     * The new owner is an id, checked against the members of the watchlist.
+     */
+    fn validate(&self) -> Vec<FieldError> {
+        Vec::new()
+    }
+}
+
+impl Validate for MediaQuery {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_limit(&mut errors, self.limit, MEDIA_PAGE_MAX_LIMIT);
+
+        errors
+    }
+}
+
+impl Validate for SearchQuery {
+    fn normalize(&mut self) {
+        self.q = self.q.trim().to_string();
+    }
+
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.q.is_empty() || self.q.chars().count() > SEARCH_MAX_QUERY_LENGTH {
+            warn!("The search is invalid.");
+            errors.push(FieldError::new(
+                "q",
+                &format!(
+                    "The search must have between 1 and {} characters.",
+                    SEARCH_MAX_QUERY_LENGTH
+                ),
+            ));
+        }
+        check_limit(&mut errors, self.limit, SEARCH_MAX_LIMIT);
+
+        errors
+    }
+}
+
+// Functions
+/**
+ * A method to normalize some text to the NFC, so the same text is always stored the same way.
+*/
+pub fn normalize(value: &str) -> String {
+    value.nfc().collect()
+}
+
+/**
+ * A method to normalize some genre. The genres are compared in lowercase, like "Sci-Fi" and "sci-fi".
+*/
+pub fn normalize_genre(genre: &str) -> String {
+    normalize(genre.trim()).to_lowercase()
+}
+
+/**
+ * A method to check the limit of some page. A missing limit uses the default of the page.
+*/
+fn check_limit(errors: &mut Vec<FieldError>, limit: Option<usize>, max: usize) {
+    if limit.is_some_and(|limit| !(1..=max).contains(&limit)) {
+        warn!("The limit is invalid.");
+        errors.push(FieldError::new(
+            "limit",
+            &format!("The limit must be between 1 and {}.", max),
+        ));
+    }
+}
+
+/**
+ * A method to get the SurrealQL conditions of the rules, by their name.
+ * The migrations write them as they are, so a changed rule needs a new migration redefining its fields.
+*/
+pub fn conditions() -> Vec<(&'static str, String)> {
+    let fields = FIELD_RULES.iter().map(|rule| (rule.name, rule.surql()));
+    let ranges = RANGE_RULES.iter().map(|rule| (rule.name, rule.surql()));
+    fields
+        .chain(ranges)
+        .chain([("imdb_id", format!("/{}/", IMDB_ID_PATTERN))])
+        .collect()
+}
+
+/**
+ * A method to get the key of some username. The usernames are unique by their key.
+ * The full case folding goes beyond the lowercase, so "Straße" and "STRASSE" have the same key.
+*/
+pub fn username_key(username: &str) -> String {
+    let decomposed: String = username.nfd().collect();
+    default_case_fold_str(&decomposed).nfc().collect()
+}
@@ -0,0 +1,314 @@
+// Not every test file uses all the helpers.
+#![allow(dead_code)]
+
+// Libs
+use std::{future::Future, net::SocketAddr};
+
+use api::{
+    config::{initialize_config, Config, LimitsConfig},
+    database::{initialize_db, DATABASE},
+    limiter::{initialize_limiter, LimiterConfig},
+    router::get_router,
+    security::initialize_security,
+};
+use axum::{
+    body::{to_bytes, Body},
+    extract::connect_info::MockConnectInfo,
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use surrealdb::sql::Thing;
+use tokio::{runtime::Runtime, sync::OnceCell};
+use tower::ServiceExt;
+
+// Data
+/**
+ * The runtime shared by all the tests of a file.
+ * The embedded database lives in the runtime that connected it, so it can't be dropped between the tests.
+*/
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Couldn\'t build the runtime.")
+});
+static INITIALIZED: OnceCell<()> = OnceCell::const_new();
+pub static PASSWORD: &str = "some-password";
+
+// Structs
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+pub struct TestUser {
+    pub id: String,
+    pub username: String,
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+// Implementations
+impl TestResponse {
+    /**
+     * A method to get the data field of the response body.
+     */
+    pub fn data(&self) -> &Value {
+        &self.body["data"]
+    }
+}
+
+// Functions
+/**
+ * A method to run some test against the API. The API is initialized with an in-memory database once per file.
+*/
+pub fn run<F: Future<Output = ()>>(test: F) {
+    let rate_limit = LimiterConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    run_with_rate_limit(rate_limit, test);
+}
+
+/**
+ * A method to run some test against the API with the given rate limit.
+ * The API is initialized once per file, so all the tests of the file must use the same rate limit.
+*/
+pub fn run_with_rate_limit<F: Future<Output = ()>>(rate_limit: LimiterConfig, test: F) {
+    RUNTIME.block_on(async {
+        INITIALIZED
+            .get_or_init(|| initialize(rate_limit, LimitsConfig::default()))
+            .await;
+        test.await;
+    });
+}
+
+/**
+ * A method to run some test against the API with the given limits and without rate limit.
+ * The API is initialized once per file, so all the tests of the file must use the same limits.
+*/
+pub fn run_with_limits<F: Future<Output = ()>>(limits: LimitsConfig, test: F) {
+    let rate_limit = LimiterConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    RUNTIME.block_on(async {
+        INITIALIZED
+            .get_or_init(|| initialize(rate_limit, limits))
+            .await;
+        test.await;
+    });
+}
+
+/**
+ * A method to initialize the API as the main does, with the given rate limit and limits.
+*/
+async fn initialize(rate_limit: LimiterConfig, limits: LimitsConfig) {
+    let mut config = Config::default();
+    config.database.uri = Some(String::from("mem://"));
+    config.security.token_secret = Some(String::from("0123456789abcdef0123456789abcdef"));
+    config.rate_limit = rate_limit;
+    config.limits = limits;
+    initialize_config(config.clone());
+
+    initialize_security(&config.security).expect("Couldn\'t initialize the security.");
+    initialize_limiter(config.rate_limit).expect("Couldn\'t initialize the rate limiter.");
+    initialize_db(&config.database)
+        .await
+        .expect("Couldn\'t initialize the database.");
+}
+
+/**
+ * A method to get the router with a mocked client address, as `into_make_service_with_connect_info` does.
+*/
+pub fn app() -> Router {
+    get_router().layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 3000))))
+}
+
+/**
+ * A method to send some request to the API and parse its JSON body.
+*/
+pub async fn send(req: Request<Body>) -> TestResponse {
+    let res = app()
+        .oneshot(req)
+        .await
+        .expect("Couldn\'t send the request.");
+    let status = res.status();
+    let headers = res.headers().clone();
+    let bytes = to_bytes(res.into_body(), usize::MAX)
+        .await
+        .expect("Couldn\'t read the body.");
+    let body = match bytes.is_empty() {
+        true => Value::Null,
+        false => serde_json::from_slice(&bytes).expect("The body isn\'t a valid JSON."),
+    };
+
+    TestResponse {
+        status,
+        headers,
+        body,
+    }
+}
+
+/**
+ * A method to build some request. The access token and the body are optional.
+*/
+pub fn request(
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::ACCEPT, "application/json");
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+
+    match body {
+        None => builder.body(Body::empty()),
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+    }
+    .expect("Couldn\'t build the request.")
+}
+
+pub async fn get(uri: &str, token: Option<&str>) -> TestResponse {
+    send(request(Method::GET, uri, token, None)).await
+}
+
+pub async fn post(uri: &str, token: Option<&str>, body: Option<Value>) -> TestResponse {
+    send(request(Method::POST, uri, token, body)).await
+}
+
+pub async fn patch(uri: &str, token: Option<&str>, body: Value) -> TestResponse {
+    send(request(Method::PATCH, uri, token, Some(body))).await
+}
+
+pub async fn delete(uri: &str, token: Option<&str>) -> TestResponse {
+    send(request(Method::DELETE, uri, token, None)).await
+}
+
+/**
+ * A method to login some user using the Basic authorization.
+*/
+pub async fn login(username: &str, password: &str) -> TestResponse {
+    let credentials = STANDARD.encode(format!("{}:{}", username, password));
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/user/login")
+        .header(header::ACCEPT, "application/json")
+        .header(header::AUTHORIZATION, format!("Basic {}", credentials))
+        .body(Body::empty())
+        .expect("Couldn\'t build the request.");
+    send(req).await
+}
+
+/**
+ * A method to create a new user and login it.
+*/
+pub async fn create_user(username: &str) -> TestUser {
+    let res = post(
+        "/user",
+        None,
+        Some(json!({ "username": username, "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+
+    let res = login(username, PASSWORD).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    TestUser {
+        id: string(&res.data()["user"]["id"]),
+        username: String::from(username),
+        access_token: string(&res.data()["access_token"]),
+        refresh_token: string(&res.data()["refresh_token"]),
+    }
+}
+
+/**
+ * A method to create a new watchlist owned by the given user. Returns its id.
+*/
+pub async fn create_watchlist(owner: &TestUser, title: &str) -> String {
+    let res = post(
+        "/watchlist",
+        Some(&owner.access_token),
+        Some(json!({ "title": title, "description": "Some description" })),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+    string(&res.data()["id"])
+}
+
+/**
+ * A method to create a new media in the given watchlist. Returns its id.
+*/
+pub async fn create_media(user: &TestUser, watchlist_id: &str, title: &str) -> String {
+    let res = post(
+        "/media",
+        Some(&user.access_token),
+        Some(json!({
+            "title": title,
+            "description": "Some description",
+            "watchlist": watchlist_id,
+            "watched": false,
+        })),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+    string(&res.data()["id"])
+}
+
+/**
+ * A method to invite some user to the watchlist and accept the invitation.
+*/
+pub async fn add_member(owner: &TestUser, watchlist_id: &str, member: &TestUser, role: &str) {
+    let res = post(
+        &format!("/watchlist/{}/invitation", watchlist_id),
+        Some(&owner.access_token),
+        Some(json!({ "username": member.username, "role": role })),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+
+    let invitation_id = string(&res.data()["id"]);
+    let res = post(
+        &format!("/invitation/{}/accept", invitation_id),
+        Some(&member.access_token),
+        None,
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+}
+
+/**
+ * A method to get some JSON value as a String.
+*/
+pub fn string(value: &Value) -> String {
+    value
+        .as_str()
+        .unwrap_or_else(|| panic!("{} isn\'t a string.", value))
+        .to_string()
+}
+
+/**
+ * A method to check if some record exists in the database.
+*/
+pub async fn exists(table: &str, id: &str) -> bool {
+    let ids: Vec<Thing> = DATABASE
+        .query("SELECT VALUE id FROM type::thing($table, $id)")
+        .bind(("table", table))
+        .bind(("id", id))
+        .await
+        .expect("Couldn\'t get the record.")
+        .take(0)
+        .expect("Couldn\'t get the record.");
+    !ids.is_empty()
+}
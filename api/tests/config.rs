@@ -0,0 +1,186 @@
+// Libs
+use std::{fs, path::PathBuf, time::Duration};
+
+use api::config::{Cli, Command, Config};
+use api::migrations::MigrationMode;
+use clap::Parser;
+
+// Data
+static TOKEN_SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+// Functions
+/**
+ * A method to write some config file in the temp dir. Returns its path.
+*/
+fn write_config(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("watchlist-{}-{}.toml", name, std::process::id()));
+    fs::write(&path, content).expect("Couldn\'t write the config file.");
+    path
+}
+
+/**
+ * A method to get a valid configuration using the embedded database.
+*/
+fn valid_config() -> Config {
+    let mut config = Config::default();
+    config.database.uri = Some(String::from("mem://"));
+    config.security.token_secret = Some(String::from(TOKEN_SECRET));
+    config
+}
+
+// Tests
+#[test]
+fn default_config_needs_the_database_uri_and_the_token_secret() {
+    let mut config = Config::default();
+    assert!(config.validate().is_err());
+
+    config.database.uri = Some(String::from("mem://"));
+    assert!(config.validate().is_err());
+
+    config.security.token_secret = Some(String::from(TOKEN_SECRET));
+    assert!(config.validate().is_ok());
+    assert_eq!(config.server.port, 3000);
+    assert_eq!(config.server.request_timeout, Duration::from_secs(10));
+    assert_eq!(config.server.shutdown_timeout, Duration::from_secs(30));
+    assert_eq!(config.database.namespace, "watchlist");
+    assert_eq!(config.database.name, "api");
+}
+
+#[test]
+fn file_values_override_the_defaults() {
+    let path = write_config(
+        "file",
+        r#"
+            [server]
+            port = 3001
+            request_timeout_secs = 5
+
+            [database]
+            uri = "mem://"
+            namespace = "other"
+
+            [rate_limit]
+            login_lockout_base_secs = 10
+        "#,
+    );
+
+    let config = Config::from_file(&path).unwrap();
+    assert_eq!(config.server.port, 3001);
+    assert_eq!(config.server.request_timeout, Duration::from_secs(5));
+    assert_eq!(config.database.uri.as_deref(), Some("mem://"));
+    assert_eq!(config.database.namespace, "other");
+    assert_eq!(config.database.name, "api");
+    assert_eq!(
+        config.rate_limit.login_lockout_base,
+        Duration::from_secs(10)
+    );
+    assert_eq!(config.rate_limit.ip_burst, 60);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unknown_keys_in_the_file_are_rejected() {
+    let path = write_config("unknown", "[server]\nprot = 3001\n");
+    assert!(Config::from_file(&path).is_err());
+    fs::remove_file(path).unwrap();
+
+    assert!(Config::from_file(&PathBuf::from("/nonexistent/config.toml")).is_err());
+}
+
+#[test]
+fn env_vars_override_the_file_and_flags_override_the_env_vars() {
+    let path = write_config(
+        "layers",
+        &format!(
+            "[server]\nport = 3001\n\n[database]\nuri = \"mem://\"\nname = \"file\"\n\n[security]\ntoken_secret = \"{}\"\n",
+            TOKEN_SECRET
+        ),
+    );
+    std::env::set_var("SERVER_PORT", "3002");
+    std::env::set_var("DATABASE_NAME", "env");
+    std::env::set_var("TRASH_RETENTION_DAYS", "7");
+
+    let cli =
+        Cli::try_parse_from(["api", "--config", path.to_str().unwrap(), "--port", "3003"]).unwrap();
+    let config = Config::load(&cli);
+
+    std::env::remove_var("SERVER_PORT");
+    std::env::remove_var("DATABASE_NAME");
+    std::env::remove_var("TRASH_RETENTION_DAYS");
+    fs::remove_file(path).unwrap();
+
+    let config = config.unwrap();
+    assert_eq!(config.server.port, 3003);
+    assert_eq!(config.database.name, "env");
+    assert_eq!(config.trash.retention_days, 7);
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    let mut config = valid_config();
+    config.limits.title_max_length = 21;
+    assert!(config.validate().is_err());
+
+    let mut config = valid_config();
+    config.security.token_secret = Some(String::from("short"));
+    assert!(config.validate().is_err());
+
+    let mut config = valid_config();
+    config.database.uri = Some(String::from("ws://localhost:8000"));
+    assert!(config.validate().is_err());
+    config.database.user = Some(String::from("root"));
+    config.database.pass = Some(String::from("root"));
+    assert!(config.validate().is_ok());
+
+    let mut config = valid_config();
+    config.database.health_check_interval = Duration::ZERO;
+    assert!(config.validate().is_err());
+
+    let mut config = valid_config();
+    config.log.level = String::from("loud");
+    assert!(config.validate().is_err());
+
+    let mut config = valid_config();
+    config.telemetry.otlp_endpoint = Some(String::from("otel-collector:4318"));
+    assert!(config.validate().is_err());
+    config.telemetry.otlp_endpoint = Some(String::from("http://otel-collector:4318"));
+    assert!(config.validate().is_ok());
+    config.telemetry.sample_ratio = 1.5;
+    assert!(config.validate().is_err());
+
+    let mut config = valid_config();
+    config.trash.purge_interval = Duration::ZERO;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn print_config_redacts_the_secrets() {
+    let mut config = valid_config();
+    config.database.pass = Some(String::from("database-password"));
+
+    let dump = config.to_redacted_toml();
+    assert!(!dump.contains(TOKEN_SECRET));
+    assert!(!dump.contains("database-password"));
+
+    // The dump can be used as a config file.
+    let path = write_config("dump", &dump);
+    let loaded = Config::from_file(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    assert_eq!(loaded.server.port, config.server.port);
+    assert_eq!(loaded.database.uri, config.database.uri);
+}
+
+#[test]
+fn migrations_subcommand_is_parsed() {
+    let cli = Cli::try_parse_from(["api", "migrations", "dry-run"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Some(Command::Migrations {
+            mode: MigrationMode::DryRun
+        })
+    ));
+
+    let cli = Cli::try_parse_from(["api"]).unwrap();
+    assert!(cli.command.is_none());
+    assert!(Cli::try_parse_from(["api", "migrations", "rollback"]).is_err());
+}
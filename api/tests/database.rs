@@ -0,0 +1,46 @@
+// Libs
+use std::time::Duration;
+
+use api::database::{backoff, database_state, set_database_state, DatabaseState};
+use axum::http::StatusCode;
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn backoff_doubles_up_to_the_max() {
+    let max = Duration::from_secs(30);
+    let delays: Vec<u64> = (0..7)
+        .map(|attempt| backoff(attempt, max).as_secs())
+        .collect();
+    assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30]);
+    assert_eq!(backoff(u32::MAX, max), max);
+}
+
+#[test]
+fn degraded_database_answers_service_unavailable() {
+    run(async {
+        let user = create_user("dbdegraded").await;
+        assert_eq!(database_state(), DatabaseState::Connected);
+
+        set_database_state(DatabaseState::Degraded);
+        let res = get("/watchlist", Some(&user.access_token)).await;
+        assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            res.body["message"],
+            "The database is unavailable. Try again later."
+        );
+        assert_eq!(res.body["code"], "DATABASE_UNAVAILABLE");
+
+        // The API is still alive, but not ready.
+        assert_eq!(get("/healthz", None).await.status, StatusCode::OK);
+        let res = get("/readyz", None).await;
+        assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.data()["database"], false);
+
+        set_database_state(DatabaseState::Connected);
+        let res = get("/watchlist", Some(&user.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+    });
+}
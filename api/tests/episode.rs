@@ -0,0 +1,291 @@
+// Libs
+use api::database::{cleanup_orphans, DATABASE};
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::*;
+mod common;
+
+// Functions
+/**
+ * A method to create a series in the watchlist.
+*/
+async fn create_series(user: &TestUser, watchlist_id: &str, title: &str) -> String {
+    let res = post(
+        "/media",
+        Some(&user.access_token),
+        Some(json!({
+            "title": title,
+            "description": "Some series",
+            "watchlist": watchlist_id,
+            "watched": false,
+            "kind": "series",
+        })),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+    string(&res.data()["id"])
+}
+
+/**
+ * A method to add an episode to the media.
+*/
+async fn create_episode(user: &TestUser, media_id: &str, season: u16, number: u16) -> String {
+    let res = post(
+        &format!("/media/{}/episode", media_id),
+        Some(&user.access_token),
+        Some(json!({ "season": season, "number": number })),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+    string(&res.data()["id"])
+}
+
+// Tests
+#[test]
+fn post_episode_requires_an_editor_of_a_series() {
+    run(async {
+        let owner = create_user("epcreateowner").await;
+        let viewer = create_user("epcreateviewer").await;
+        let watchlist_id = create_watchlist(&owner, "Episodes").await;
+        add_member(&owner, &watchlist_id, &viewer, "viewer").await;
+        let series_id = create_series(&owner, &watchlist_id, "Dark").await;
+        let movie_id = create_media(&owner, &watchlist_id, "Alien").await;
+        let uri = format!("/media/{}/episode", series_id);
+        let body = json!({ "season": 1, "number": 1, "title": "Secrets" });
+
+        let res = post(&uri, Some(&viewer.access_token), Some(body.clone())).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = post(&uri, Some(&owner.access_token), Some(body.clone())).await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+        assert_eq!(res.data()["season"], 1);
+        assert_eq!(res.data()["title"], "Secrets");
+        assert_eq!(res.data()["watched_at"], Value::Null);
+
+        let res = post(&uri, Some(&owner.access_token), Some(body.clone())).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "EPISODE_EXISTS");
+
+        let res = post(
+            &format!("/media/{}/episode", movie_id),
+            Some(&owner.access_token),
+            Some(body),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "MEDIA_NOT_EPISODIC");
+
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "season": 101, "number": 0 })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_FIELDS");
+
+        let res = get(&uri, Some(&viewer.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data().as_array().unwrap().len(), 1);
+    });
+}
+
+#[test]
+fn the_progress_of_a_series_follows_its_episodes() {
+    run(async {
+        let owner = create_user("epprogressowner").await;
+        let watchlist_id = create_watchlist(&owner, "Progress").await;
+        let series_id = create_series(&owner, &watchlist_id, "Dark").await;
+        let mut episodes = Vec::new();
+        for (season, number) in [(1, 1), (1, 2), (1, 3), (2, 1), (2, 2), (2, 3), (2, 4)] {
+            episodes.push(create_episode(&owner, &series_id, season, number).await);
+        }
+        let media_uri = format!("/media/{}", series_id);
+
+        let res = get(&media_uri, Some(&owner.access_token)).await;
+        assert_eq!(res.data()["progress"]["summary"], "0%");
+        assert_eq!(res.data()["watched"], false);
+
+        // Watch the first season and an episode of the second.
+        let res = post(
+            &format!("/media/{}/season/1/watched", series_id),
+            Some(&owner.access_token),
+            Some(json!({ "watched": true })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        assert_eq!(res.data()["progress"]["last_watched"], "S01E03");
+
+        let res = post(
+            &format!("/media/{}/episode/{}/watched", series_id, episodes[4]),
+            Some(&owner.access_token),
+            Some(json!({ "watched": true })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        assert_eq!(
+            res.data()["progress"],
+            json!({
+                "watched": 4,
+                "total": 7,
+                "percent": 57,
+                "last_watched": "S02E02",
+                "summary": "S02E02, 57%",
+            })
+        );
+
+        // The watched of the series is derived from its episodes.
+        let res = post(
+            &format!("/media/{}/season/2/watched", series_id),
+            Some(&owner.access_token),
+            Some(json!({ "watched": true })),
+        )
+        .await;
+        assert_eq!(res.data()["watched"], true);
+        assert_eq!(res.data()["progress"]["summary"], "S02E04, 100%");
+
+        let res = patch(
+            &media_uri,
+            Some(&owner.access_token),
+            json!({
+                "title": "Dark",
+                "description": "Some series",
+                "watchlist": watchlist_id,
+                "watched": false,
+                "kind": "series",
+            }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        assert_eq!(res.data()["watched"], true);
+
+        // The episodes would be lost in a movie.
+        let res = patch(
+            &media_uri,
+            Some(&owner.access_token),
+            json!({
+                "title": "Dark",
+                "description": "Some series",
+                "watchlist": watchlist_id,
+                "watched": false,
+                "kind": "movie",
+            }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "MEDIA_HAS_EPISODES");
+        let res = get(&media_uri, Some(&owner.access_token)).await;
+        assert_eq!(res.data()["kind"], "series");
+
+        let res = delete(
+            &format!("/media/{}/episode/{}", series_id, episodes[6]),
+            Some(&owner.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        assert_eq!(res.data()["progress"]["total"], 6);
+
+        let res = post(
+            &format!("/media/{}/season/2/watched", series_id),
+            Some(&owner.access_token),
+            Some(json!({ "watched": false })),
+        )
+        .await;
+        assert_eq!(res.data()["watched"], false);
+        assert_eq!(res.data()["progress"]["summary"], "S01E03, 50%");
+
+        let res = post(
+            &format!("/media/{}/season/3/watched", series_id),
+            Some(&owner.access_token),
+            Some(json!({ "watched": true })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+        assert_eq!(res.body["code"], "SEASON_NOT_FOUND");
+
+        for uri in [
+            format!("/media/{}/season/1/watched", series_id),
+            format!("/media/{}/episode/{}/watched", series_id, episodes[0]),
+        ] {
+            let res = post(
+                &uri,
+                Some(&owner.access_token),
+                Some(json!({ "watched": "yes" })),
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", uri);
+            assert_eq!(res.body["code"], "INVALID_BODY", "{}", uri);
+        }
+
+        for season in ["one", "-1", "101"] {
+            let res = post(
+                &format!("/media/{}/season/{}/watched", series_id, season),
+                Some(&owner.access_token),
+                Some(json!({ "watched": true })),
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", season);
+            assert_eq!(res.body["code"], "INVALID_FIELDS", "{}", season);
+            assert_eq!(res.body["details"][0]["field"], "season", "{}", season);
+        }
+    });
+}
+
+#[test]
+fn the_episodes_belong_to_their_media() {
+    run(async {
+        let owner = create_user("epmediaowner").await;
+        let watchlist_id = create_watchlist(&owner, "Episodes").await;
+        let dark_id = create_series(&owner, &watchlist_id, "Dark").await;
+        let lost_id = create_series(&owner, &watchlist_id, "Lost").await;
+        let episode_id = create_episode(&owner, &dark_id, 1, 1).await;
+
+        let res = post(
+            &format!("/media/{}/episode/{}/watched", lost_id, episode_id),
+            Some(&owner.access_token),
+            Some(json!({ "watched": true })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+        assert_eq!(res.body["code"], "EPISODE_NOT_FOUND");
+
+        let res = delete(
+            &format!("/media/{}/episode/{}", lost_id, episode_id),
+            Some(&owner.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+    });
+}
+
+#[test]
+fn cleanup_orphans_removes_the_episodes_of_deleted_medias() {
+    run(async {
+        let owner = create_user("eporphans").await;
+        let watchlist_id = create_watchlist(&owner, "Orphans").await;
+        let series_id = create_series(&owner, &watchlist_id, "Dark").await;
+        create_episode(&owner, &series_id, 1, 1).await;
+
+        // Delete the watchlist without its cascade, like the old deletes did.
+        DATABASE
+            .query("DELETE type::thing('watchlist', $id)")
+            .bind(("id", &watchlist_id))
+            .await
+            .expect("Couldn\'t delete the watchlist.");
+
+        let report = cleanup_orphans()
+            .await
+            .expect("Couldn\'t clean up the orphans.");
+        assert_eq!(report.medias, 1);
+        assert_eq!(report.episodes, 1);
+        let episodes: Option<usize> = DATABASE
+            .query("SELECT VALUE count() FROM episode WHERE media = type::thing('media', $id) GROUP ALL")
+            .bind(("id", &series_id))
+            .await
+            .expect("Couldn\'t count the episodes.")
+            .take(0)
+            .expect("Couldn\'t count the episodes.");
+        assert_eq!(episodes.unwrap_or_default(), 0);
+    });
+}
@@ -0,0 +1,78 @@
+// Libs
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn the_errors_have_a_stable_code() {
+    run(async {
+        create_user("errorcode").await;
+
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "errorcode", "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["status"], "Failed");
+        assert_eq!(res.body["code"], "USERNAME_TAKEN");
+        assert!(res.body["message"].is_string());
+        assert!(res.body.get("details").is_none());
+
+        let res = get("/user/unknown", None).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+        assert_eq!(res.body["code"], "USER_NOT_FOUND");
+
+        let res = get("/watchlist", Some("invalid")).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_TOKEN");
+    });
+}
+
+#[test]
+fn the_invalid_fields_are_all_detailed() {
+    run(async {
+        let user = create_user("errorfields").await;
+
+        let res = post(
+            "/watchlist",
+            Some(&user.access_token),
+            Some(json!({ "title": "x", "description": "" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_FIELDS");
+        let fields: Vec<&str> = res.body["details"]
+            .as_array()
+            .expect("The details are missing.")
+            .iter()
+            .map(|detail| detail["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, ["title", "description"]);
+    });
+}
+
+#[test]
+fn the_forbidden_errors_name_the_resource() {
+    run(async {
+        let owner = create_user("errorowner").await;
+        let stranger = create_user("errorstranger").await;
+        let watchlist_id = create_watchlist(&owner, "Private").await;
+
+        let res = get(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&stranger.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+        assert_eq!(res.body["code"], "FORBIDDEN_WATCHLIST");
+
+        let res = delete("/watchlist/unknown", Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+        assert_eq!(res.body["code"], "WATCHLIST_NOT_FOUND");
+    });
+}
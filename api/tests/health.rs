@@ -0,0 +1,42 @@
+// Libs
+use api::migrations::latest_version;
+use axum::http::{header, Method, StatusCode};
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn get_healthz_skips_the_accept_header_and_the_auth() {
+    run(async {
+        let mut req = request(Method::GET, "/healthz", None, None);
+        req.headers_mut()
+            .insert(header::ACCEPT, "text/plain".parse().unwrap());
+
+        let res = send(req).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.body["status"], "Success");
+    });
+}
+
+#[test]
+fn get_readyz_checks_the_database_and_the_migrations() {
+    run(async {
+        let res = get("/readyz", None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["database"], true);
+        assert_eq!(res.data()["migration_version"], latest_version());
+        assert_eq!(res.data()["expected_migration_version"], latest_version());
+    });
+}
+
+#[test]
+fn get_version_returns_the_build() {
+    run(async {
+        let res = get("/version", None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["name"], env!("CARGO_PKG_NAME"));
+        assert_eq!(res.data()["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(res.data()["migration_version"], latest_version());
+    });
+}
@@ -0,0 +1,214 @@
+// Libs
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::*;
+mod common;
+
+// Functions
+/**
+ * A method to invite some user to the watchlist. Returns the invitation id.
+*/
+async fn invite(inviter: &TestUser, watchlist_id: &str, invitee: &TestUser, role: &str) -> String {
+    let res = post(
+        &format!("/watchlist/{}/invitation", watchlist_id),
+        Some(&inviter.access_token),
+        Some(json!({ "username": invitee.username, "role": role })),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+    string(&res.data()["id"])
+}
+
+// Tests
+#[test]
+fn post_watchlist_invitation_creates_a_pending_invitation() {
+    run(async {
+        let owner = create_user("invcreateowner").await;
+        let invitee = create_user("invcreateinvitee").await;
+        let watchlist_id = create_watchlist(&owner, "Invitations").await;
+        let uri = format!("/watchlist/{}/invitation", watchlist_id);
+
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "username": invitee.username, "role": "editor" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED);
+        assert_eq!(res.data()["status"], "pending");
+        assert_eq!(res.data()["invitee"], invitee.id.as_str());
+        assert_eq!(res.data()["inviter"], owner.id.as_str());
+
+        // The user already has a pending invitation.
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "username": invitee.username, "role": "viewer" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "username": "nobody", "role": "viewer" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+    });
+}
+
+#[test]
+fn post_watchlist_invitation_rejects_the_invalid_bodies() {
+    run(async {
+        let owner = create_user("invbodyowner").await;
+        let watchlist_id = create_watchlist(&owner, "Invitations").await;
+        let uri = format!("/watchlist/{}/invitation", watchlist_id);
+
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "username": "invbodyowner", "role": "king" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_BODY");
+
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "username": "x", "role": "viewer" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_FIELDS");
+        assert_eq!(res.body["details"][0]["field"], "username");
+    });
+}
+
+#[test]
+fn post_watchlist_invitation_checks_the_inviter_role() {
+    run(async {
+        let owner = create_user("invroleowner").await;
+        let admin = create_user("invroleadmin").await;
+        let editor = create_user("invroleeditor").await;
+        let invitee = create_user("invroleinvitee").await;
+        let watchlist_id = create_watchlist(&owner, "Invitations").await;
+        add_member(&owner, &watchlist_id, &admin, "admin").await;
+        add_member(&owner, &watchlist_id, &editor, "editor").await;
+        let uri = format!("/watchlist/{}/invitation", watchlist_id);
+
+        let res = post(
+            &uri,
+            Some(&editor.access_token),
+            Some(json!({ "username": invitee.username, "role": "viewer" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        // Only the owner can invite an admin.
+        let res = post(
+            &uri,
+            Some(&admin.access_token),
+            Some(json!({ "username": invitee.username, "role": "admin" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = post(
+            &uri,
+            Some(&admin.access_token),
+            Some(json!({ "username": editor.username, "role": "viewer" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+
+        invite(&admin, &watchlist_id, &invitee, "editor").await;
+    });
+}
+
+#[test]
+fn get_invitations_returns_the_pending_invitations() {
+    run(async {
+        let owner = create_user("invlistowner").await;
+        let invitee = create_user("invlistinvitee").await;
+        let watchlist_id = create_watchlist(&owner, "Invitations").await;
+        let invitation_id = invite(&owner, &watchlist_id, &invitee, "viewer").await;
+
+        let res = get("/invitation", Some(&invitee.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data().as_array().unwrap().len(), 1);
+        assert_eq!(res.data()[0]["id"], invitation_id.as_str());
+
+        let res = get("/invitation", Some(&owner.access_token)).await;
+        assert_eq!(res.data(), &json!([]));
+    });
+}
+
+#[test]
+fn post_invitation_accept_adds_the_member() {
+    run(async {
+        let owner = create_user("invacceptowner").await;
+        let invitee = create_user("invacceptinvitee").await;
+        let watchlist_id = create_watchlist(&owner, "Invitations").await;
+        let invitation_id = invite(&owner, &watchlist_id, &invitee, "editor").await;
+        let uri = format!("/invitation/{}/accept", invitation_id);
+
+        let res = post(&uri, Some(&owner.access_token), None).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = post(&uri, Some(&invitee.access_token), None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(
+            res.data()["members"],
+            json!([{ "user": invitee.id, "role": "editor" }])
+        );
+
+        let res = post(&uri, Some(&invitee.access_token), None).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        let res = get("/invitation", Some(&invitee.access_token)).await;
+        assert_eq!(res.data(), &json!([]));
+    });
+}
+
+#[test]
+fn post_invitation_decline_doesnt_add_the_member() {
+    run(async {
+        let owner = create_user("invdeclineowner").await;
+        let invitee = create_user("invdeclineinvitee").await;
+        let watchlist_id = create_watchlist(&owner, "Invitations").await;
+        let invitation_id = invite(&owner, &watchlist_id, &invitee, "editor").await;
+
+        let res = post(
+            &format!("/invitation/{}/decline", invitation_id),
+            Some(&invitee.access_token),
+            None,
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["status"], "declined");
+
+        let res = post(
+            &format!("/invitation/{}/accept", invitation_id),
+            Some(&invitee.access_token),
+            None,
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        let res = get(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&invitee.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = post(
+            "/invitation/unknown/decline",
+            Some(&invitee.access_token),
+            None,
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+    });
+}
@@ -0,0 +1,81 @@
+// Libs
+use api::{config::LimitsConfig, database::DATABASE, security::hash_password};
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::*;
+mod common;
+
+// Functions
+/**
+ * A method to run some test with the username limit lowered to 10.
+*/
+fn run_lowered<F: std::future::Future<Output = ()>>(test: F) {
+    let limits = LimitsConfig {
+        username_max_length: 10,
+        ..Default::default()
+    };
+    run_with_limits(limits, test);
+}
+
+/**
+ * A method to create some user as if it was created before the limit was lowered.
+*/
+async fn create_existing_user(username: &str) {
+    DATABASE
+        .query("CREATE user CONTENT { username: $username, username_key: $username, password: $password, token_version: 0, created_at: time::now(), updated_at: time::now() }")
+        .bind(("username", username))
+        .bind(("password", hash_password(PASSWORD.as_bytes())))
+        .await
+        .expect("Couldn\'t create the user.")
+        .check()
+        .expect("Couldn\'t create the user.");
+}
+
+// Tests
+#[test]
+fn a_lower_username_limit_only_applies_to_the_new_usernames() {
+    run_lowered(async {
+        create_existing_user("existinglonguser").await;
+
+        // The existing user can still login, keep its username and be invited.
+        let res = login("existinglonguser", PASSWORD).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        let access_token = string(&res.data()["access_token"]);
+        let res = patch(
+            "/user",
+            Some(&access_token),
+            json!({ "username": "ExistingLongUser", "password": PASSWORD }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+        let owner = create_user("limitowner").await;
+        let watchlist_id = create_watchlist(&owner, "Limits").await;
+        let res = post(
+            &format!("/watchlist/{}/invitation", watchlist_id),
+            Some(&owner.access_token),
+            Some(json!({ "username": "existinglonguser", "role": "viewer" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+
+        // The new usernames follow the lower limit.
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "newlongusername", "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_FIELDS");
+        let res = patch(
+            "/user",
+            Some(&access_token),
+            json!({ "username": "renamedlonguser", "password": PASSWORD }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_FIELDS");
+    });
+}
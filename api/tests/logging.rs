@@ -0,0 +1,115 @@
+// Libs
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::Body,
+    http::{header, Request},
+};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use tower::ServiceExt;
+
+use common::*;
+mod common;
+
+// Data
+/**
+ * The log lines written by the API in this file.
+*/
+static LOGS: Lazy<Arc<Mutex<Vec<u8>>>> = Lazy::new(|| {
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let writer = logs.clone();
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_writer(move || LogWriter(writer.clone()))
+        .init();
+    logs
+});
+
+// Structs
+struct LogWriter(Arc<Mutex<Vec<u8>>>);
+
+// Implementations
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// Functions
+/**
+ * A method to send some request with the given X-Request-Id. Returns the X-Request-Id of the response.
+*/
+async fn send_with_request_id(token: &str, request_id: Option<&str>) -> String {
+    let mut req = Request::builder()
+        .uri("/watchlist")
+        .header(header::ACCEPT, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token));
+    if let Some(request_id) = request_id {
+        req = req.header("x-request-id", request_id);
+    }
+
+    let res = app()
+        .oneshot(req.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    res.headers()["x-request-id"].to_str().unwrap().to_string()
+}
+
+// Tests
+#[test]
+fn request_id_is_propagated_or_generated() {
+    run(async {
+        let user = create_user("logrequestid").await;
+
+        let request_id = send_with_request_id(&user.access_token, Some("client-id-1")).await;
+        assert_eq!(request_id, "client-id-1");
+
+        let first = send_with_request_id(&user.access_token, None).await;
+        let second = send_with_request_id(&user.access_token, None).await;
+        assert_eq!(first.len(), 26);
+        assert_ne!(first, second);
+
+        // The invalid ids are replaced.
+        let request_id = send_with_request_id(&user.access_token, Some("with spaces")).await;
+        assert_ne!(request_id, "with spaces");
+    });
+}
+
+#[test]
+fn model_logs_carry_the_request_span() {
+    run(async {
+        Lazy::force(&LOGS);
+        let user = create_user("logspan").await;
+        send_with_request_id(&user.access_token, Some("span-request-id")).await;
+
+        let logs = String::from_utf8(LOGS.lock().unwrap().clone()).unwrap();
+        let lines: Vec<Value> = logs
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .filter(|line: &Value| line["span"]["request_id"] == "span-request-id")
+            .collect();
+
+        // The line of the model layer is tied to the request and to the user.
+        let model_line = lines
+            .iter()
+            .find(|line| {
+                line["fields"]["message"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .starts_with("Getting all watchlists from")
+            })
+            .expect("The model line wasn\'t logged in the request span.");
+        assert_eq!(model_line["span"]["route"], "/watchlist");
+        assert_eq!(model_line["span"]["method"], "GET");
+        assert_eq!(model_line["span"]["user_id"], format!("user:{}", user.id));
+    });
+}
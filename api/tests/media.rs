@@ -0,0 +1,287 @@
+// Libs
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn post_media_requires_an_editor() {
+    run(async {
+        let owner = create_user("mdcreateowner").await;
+        let editor = create_user("mdcreateeditor").await;
+        let viewer = create_user("mdcreateviewer").await;
+        let watchlist_id = create_watchlist(&owner, "Media").await;
+        add_member(&owner, &watchlist_id, &editor, "editor").await;
+        add_member(&owner, &watchlist_id, &viewer, "viewer").await;
+        let body = json!({
+            "title": "The Matrix",
+            "description": "Some movie",
+            "watchlist": watchlist_id,
+            "watched": false,
+        });
+
+        let res = post("/media", Some(&viewer.access_token), Some(body.clone())).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = post("/media", Some(&editor.access_token), Some(body)).await;
+        assert_eq!(res.status, StatusCode::CREATED);
+        assert_eq!(res.data()["title"], "The Matrix");
+        assert_eq!(res.data()["watchlist"], watchlist_id.as_str());
+
+        let res = post(
+            "/media",
+            Some(&editor.access_token),
+            Some(json!({
+                "title": "The Matrix",
+                "description": "Some movie",
+                "watchlist": "unknown",
+                "watched": false,
+            })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    });
+}
+
+#[test]
+fn get_media_is_only_allowed_to_members() {
+    run(async {
+        let owner = create_user("mdgetowner").await;
+        let viewer = create_user("mdgetviewer").await;
+        let stranger = create_user("mdgetstranger").await;
+        let watchlist_id = create_watchlist(&owner, "Media").await;
+        add_member(&owner, &watchlist_id, &viewer, "viewer").await;
+        let media_id = create_media(&owner, &watchlist_id, "Alien").await;
+        let uri = format!("/media/{}", media_id);
+
+        let res = get(&uri, Some(&viewer.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["title"], "Alien");
+        assert_eq!(res.data()["watched"], false);
+
+        let res = get(&uri, Some(&stranger.access_token)).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = get("/media/unknown", Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+    });
+}
+
+#[test]
+fn patch_media_requires_an_editor_of_both_watchlists() {
+    run(async {
+        let owner = create_user("mdpatchowner").await;
+        let editor = create_user("mdpatcheditor").await;
+        let viewer = create_user("mdpatchviewer").await;
+        let watchlist_id = create_watchlist(&owner, "Media").await;
+        let other_id = create_watchlist(&owner, "Other").await;
+        add_member(&owner, &watchlist_id, &editor, "editor").await;
+        add_member(&owner, &watchlist_id, &viewer, "viewer").await;
+        let media_id = create_media(&owner, &watchlist_id, "Alien").await;
+        let uri = format!("/media/{}", media_id);
+        let body = json!({
+            "title": "Aliens",
+            "description": "The sequel",
+            "watchlist": watchlist_id,
+            "watched": true,
+        });
+
+        let res = patch(&uri, Some(&viewer.access_token), body.clone()).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = patch(&uri, Some(&editor.access_token), body).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["title"], "Aliens");
+        assert_eq!(res.data()["watched"], true);
+
+        // The editor isn't a member of the other watchlist.
+        let body = json!({
+            "title": "Aliens",
+            "description": "The sequel",
+            "watchlist": other_id,
+            "watched": true,
+        });
+        let res = patch(&uri, Some(&editor.access_token), body.clone()).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = patch(&uri, Some(&owner.access_token), body).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["watchlist"], other_id.as_str());
+    });
+}
+
+#[test]
+fn delete_media_requires_an_admin() {
+    run(async {
+        let owner = create_user("mddeleteowner").await;
+        let admin = create_user("mddeleteadmin").await;
+        let editor = create_user("mddeleteeditor").await;
+        let watchlist_id = create_watchlist(&owner, "Media").await;
+        add_member(&owner, &watchlist_id, &admin, "admin").await;
+        add_member(&owner, &watchlist_id, &editor, "editor").await;
+        let media_id = create_media(&owner, &watchlist_id, "Alien").await;
+        let uri = format!("/media/{}", media_id);
+
+        let res = delete(&uri, Some(&editor.access_token)).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = delete(&uri, Some(&admin.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+
+        let res = get(&uri, Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+        let res = get(
+            &format!("/watchlist/{}/media", watchlist_id),
+            Some(&owner.access_token),
+        )
+        .await;
+        assert_eq!(res.data()["total"], 0);
+    });
+}
+
+#[test]
+fn post_media_stores_the_details() {
+    run(async {
+        let owner = create_user("mddetailsowner").await;
+        let watchlist_id = create_watchlist(&owner, "Details").await;
+
+        let res = post(
+            "/media",
+            Some(&owner.access_token),
+            Some(json!({
+                "title": "The Matrix",
+                "description": "Some movie",
+                "watchlist": watchlist_id,
+                "watched": false,
+                "kind": "movie",
+                "year": 1999,
+                "runtime": 136,
+                "genres": ["Sci-Fi", "Action", "sci-fi"],
+                "external_ids": { "imdb": "tt0133093", "tmdb": 603 },
+            })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+        assert_eq!(res.data()["kind"], "movie");
+        assert_eq!(res.data()["year"], 1999);
+        assert_eq!(res.data()["runtime"], 136);
+        assert_eq!(res.data()["genres"], json!(["sci-fi", "action"]));
+        assert_eq!(
+            res.data()["external_ids"],
+            json!({ "imdb": "tt0133093", "tmdb": 603 })
+        );
+
+        // The media without details is a movie.
+        let media_id = create_media(&owner, &watchlist_id, "Alien").await;
+        let res = get(&format!("/media/{}", media_id), Some(&owner.access_token)).await;
+        assert_eq!(res.data()["kind"], "movie");
+        assert_eq!(res.data()["genres"], json!([]));
+        assert_eq!(res.data()["external_ids"], json!({}));
+
+        let res = post(
+            "/media",
+            Some(&owner.access_token),
+            Some(json!({
+                "title": "The Matrix",
+                "description": "Some movie",
+                "watchlist": watchlist_id,
+                "watched": false,
+                "year": 1200,
+                "runtime": 0,
+                "genres": ["x"],
+                "external_ids": { "imdb": "0133093", "anilist": 0 },
+            })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        let fields: Vec<&str> = res.body["details"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|detail| detail["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "year",
+                "runtime",
+                "genres[0]",
+                "external_ids.imdb",
+                "external_ids.anilist"
+            ]
+        );
+
+        let res = post(
+            "/media",
+            Some(&owner.access_token),
+            Some(json!({
+                "title": "The Matrix",
+                "description": "Some movie",
+                "watchlist": watchlist_id,
+                "watched": false,
+                "kind": "podcast",
+            })),
+        )
+        .await;
+        assert_eq!(res.body["code"], "INVALID_BODY");
+    });
+}
+
+#[test]
+fn get_watchlist_medias_filters_by_the_details() {
+    run(async {
+        let owner = create_user("mdfilterowner").await;
+        let watchlist_id = create_watchlist(&owner, "Filters").await;
+        for (title, kind, year, genres) in [
+            ("Akira", "anime", 1988, json!(["Sci-Fi", "Action"])),
+            ("Alien", "movie", 1979, json!(["Horror", "Sci-Fi"])),
+            ("Dune", "book", 1965, json!(["Sci-Fi"])),
+            ("Heat", "movie", 1995, json!(["Crime"])),
+        ] {
+            let res = post(
+                "/media",
+                Some(&owner.access_token),
+                Some(json!({
+                    "title": title,
+                    "description": "Some media",
+                    "watchlist": watchlist_id,
+                    "watched": false,
+                    "kind": kind,
+                    "year": year,
+                    "genres": genres,
+                })),
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+        }
+        let titles = |res: &TestResponse| -> Vec<String> {
+            res.data()["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|m| string(&m["title"]))
+                .collect()
+        };
+        let uri = format!("/watchlist/{}/media?sort=title", watchlist_id);
+
+        let res = get(&format!("{}&kind=movie", uri), Some(&owner.access_token)).await;
+        assert_eq!(titles(&res), ["Alien", "Heat"]);
+        assert_eq!(res.data()["total"], 2);
+
+        let res = get(&format!("{}&genre=SCI-FI", uri), Some(&owner.access_token)).await;
+        assert_eq!(titles(&res), ["Akira", "Alien", "Dune"]);
+
+        let res = get(
+            &format!("{}&year_from=1970&year_to=1990&genre=sci-fi", uri),
+            Some(&owner.access_token),
+        )
+        .await;
+        assert_eq!(titles(&res), ["Akira", "Alien"]);
+
+        let res = get(&format!("{}&kind=podcast", uri), Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_BODY");
+    });
+}
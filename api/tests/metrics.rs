@@ -0,0 +1,60 @@
+// Libs
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+};
+use tower::ServiceExt;
+
+use common::*;
+mod common;
+
+// Functions
+/**
+ * A method to get the metrics as Prometheus does. The body is plain text, so it's returned as it is.
+*/
+async fn get_metrics() -> (StatusCode, String) {
+    let req = Request::builder()
+        .uri("/metrics")
+        .header(header::ACCEPT, "text/plain;version=0.0.4")
+        .body(Body::empty())
+        .unwrap();
+    let res = app().oneshot(req).await.unwrap();
+    let status = res.status();
+    let bytes = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+// Tests
+#[test]
+fn get_metrics_exposes_the_requests_queries_logins_and_records() {
+    run(async {
+        let owner = create_user("mtowner").await;
+        let watchlist_id = create_watchlist(&owner, "Metrics").await;
+        get(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&owner.access_token),
+        )
+        .await;
+        let res = login("mtowner", "wrong-password").await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+
+        let (status, metrics) = get_metrics().await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The routes are labeled by their path, not by the ids.
+        assert!(metrics.contains(
+            r#"http_requests_total{method="GET",route="/watchlist/:watchlist_id",status="200"}"#
+        ));
+        assert!(!metrics.contains(&watchlist_id));
+        assert!(metrics.contains(
+            r#"http_request_duration_seconds_bucket{method="POST",route="/user",status="201""#
+        ));
+        assert!(metrics.contains(
+            r#"database_query_duration_seconds_count{model="user",operation="from_username",result="ok"}"#
+        ));
+        assert!(metrics.contains(r#"login_failures_total{reason="wrong_password"} 1"#));
+        assert!(metrics.contains(r#"records_total{table="user"} 1"#));
+        assert!(metrics.contains(r#"records_total{table="watchlist"} 1"#));
+        assert!(metrics.contains(r#"records_total{table="media"} 0"#));
+    });
+}
@@ -0,0 +1,39 @@
+// Libs
+use axum::http::{header, Method, StatusCode};
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn acceptable_headers_rejects_non_json_accept() {
+    run(async {
+        let mut req = request(Method::GET, "/watchlist", None, None);
+        req.headers_mut()
+            .insert(header::ACCEPT, "text/html".parse().unwrap());
+
+        let res = send(req).await;
+        assert_eq!(res.status, StatusCode::NOT_ACCEPTABLE);
+        assert!(res.body.is_null());
+    });
+}
+
+#[test]
+fn acceptable_headers_allows_json_any_and_missing_accept() {
+    run(async {
+        let user = create_user("accept").await;
+
+        for accept in [Some("application/json"), Some("*/*"), None] {
+            let mut req = request(Method::GET, "/watchlist", Some(&user.access_token), None);
+            match accept {
+                Some(accept) => req
+                    .headers_mut()
+                    .insert(header::ACCEPT, accept.parse().unwrap()),
+                None => req.headers_mut().remove(header::ACCEPT),
+            };
+
+            let res = send(req).await;
+            assert_eq!(res.status, StatusCode::OK, "Accept: {:?}", accept);
+        }
+    });
+}
@@ -0,0 +1,49 @@
+// Libs
+use api::{
+    config::Config,
+    database::{connect_db, DATABASE},
+    migrations::{get_applied_version, latest_version, run_migrations},
+};
+use ring::digest::{digest, SHA512};
+
+// Tests
+/**
+ * The migrations of the data run over the rows of the older versions.
+ * Unlike the other test files, the rows are created before migrating, so it doesn't use the common initialization.
+*/
+#[tokio::test(flavor = "multi_thread")]
+async fn the_migrations_keep_the_rows_of_the_older_versions() {
+    let mut config = Config::default();
+    config.database.uri = Some(String::from("mem://"));
+    connect_db(&config.database)
+        .await
+        .expect("Couldn\'t connect to the database.");
+
+    // Some user created before the versioned migrations, with the schema and the SHA512 hash of that time.
+    let legacy_hash = hex::encode(digest(&SHA512, b"some-password"));
+    DATABASE
+        .query("DEFINE TABLE user SCHEMAFULL")
+        .query("DEFINE FIELD username ON TABLE user TYPE string VALUE string::lowercase($value)")
+        .query("DEFINE FIELD password ON TABLE user TYPE string ASSERT $value = /^[a-z0-9]{128}$/")
+        .query("DEFINE FIELD created_at ON TABLE user TYPE datetime")
+        .query("DEFINE FIELD updated_at ON TABLE user TYPE datetime")
+        .query("CREATE user CONTENT { username: 'legacy', password: $password, created_at: time::now(), updated_at: time::now() }")
+        .bind(("password", &legacy_hash))
+        .await
+        .expect("Couldn\'t create the legacy user.")
+        .check()
+        .expect("Couldn\'t create the legacy user.");
+
+    run_migrations()
+        .await
+        .expect("Couldn\'t run the migrations.");
+    assert_eq!(get_applied_version().await.unwrap(), latest_version());
+
+    let user: Option<(String, String, u32)> = DATABASE
+        .query("SELECT VALUE [username_key, password, token_version] FROM user WHERE username = 'legacy'")
+        .await
+        .expect("Couldn\'t get the user.")
+        .take(0)
+        .expect("Couldn\'t get the user.");
+    assert_eq!(user, Some((String::from("legacy"), legacy_hash, 0)));
+}
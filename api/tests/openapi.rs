@@ -0,0 +1,159 @@
+// Libs
+use std::collections::BTreeSet;
+
+use api::openapi::ApiDoc;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+};
+use regex::Regex;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+use common::*;
+mod common;
+
+// Data
+static ROUTER_SOURCE: &str = include_str!("../src/router.rs");
+
+// Functions
+/**
+ * A method to get the method, path and handler of each operation in the spec.
+ * The handlers are the operation ids.
+*/
+fn spec_operations() -> BTreeSet<(String, String, String)> {
+    let spec = serde_json::to_value(ApiDoc::openapi()).expect("Couldn\'t serialize the spec.");
+    let mut operations = BTreeSet::new();
+    for (path, item) in spec["paths"].as_object().expect("The spec has no paths.") {
+        for (method, operation) in item.as_object().unwrap() {
+            let handler = operation["operationId"].as_str().unwrap_or_default();
+            operations.insert((method.to_uppercase(), path.clone(), handler.to_string()));
+        }
+    }
+    operations
+}
+
+/**
+ * A method to get the method, path and handler of each route in the router.
+ * The axum path parameters are written as the OpenAPI ones.
+*/
+fn router_operations() -> BTreeSet<(String, String, String)> {
+    let route = Regex::new(r#"\.route\(\s*"([^"]+)",\s*(\w+)\((\w+)\),?\s*\)"#).unwrap();
+    let param = Regex::new(r":(\w+)").unwrap();
+    route
+        .captures_iter(ROUTER_SOURCE)
+        .map(|captures| {
+            (
+                captures[2].to_uppercase(),
+                param.replace_all(&captures[1], "{$1}").to_string(),
+                captures[3].to_string(),
+            )
+        })
+        .collect()
+}
+
+// Tests
+#[test]
+fn the_spec_describes_every_route_of_the_router() {
+    let router = router_operations();
+    let spec = spec_operations();
+    assert!(!router.is_empty(), "No route was found in the router.");
+
+    let undocumented: Vec<_> = router.difference(&spec).collect();
+    let unrouted: Vec<_> = spec.difference(&router).collect();
+    assert!(
+        undocumented.is_empty() && unrouted.is_empty(),
+        "The spec and the router diverge.\nNot in the spec: {:?}\nNot in the router: {:?}",
+        undocumented,
+        unrouted
+    );
+}
+
+#[test]
+fn every_operation_of_the_spec_is_served() {
+    run(async {
+        let param = Regex::new(r"\{\w+\}").unwrap();
+        for (method, path, handler) in spec_operations() {
+            let uri = param.replace_all(&path, "01HQZ4N7V8W3JX2K5M6P9R0STA");
+            let req = Request::builder()
+                .method(Method::from_bytes(method.as_bytes()).unwrap())
+                .uri(uri.as_ref())
+                .header(header::ACCEPT, "application/json")
+                .body(Body::empty())
+                .unwrap();
+            let res = app().oneshot(req).await.unwrap();
+            let status = res.status();
+            let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+
+            // The unmatched routes and methods are answered by axum, without body.
+            assert_ne!(
+                status,
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {} ({})",
+                method,
+                path,
+                handler
+            );
+            assert!(
+                status != StatusCode::NOT_FOUND || !body.is_empty(),
+                "{} {} ({}) isn\'t routed.",
+                method,
+                path,
+                handler
+            );
+        }
+    });
+}
+
+#[test]
+fn get_openapi_returns_the_spec() {
+    run(async {
+        let res = get("/openapi.json", None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.body["info"]["title"], "Watchlist API");
+
+        let schemas = &res.body["components"]["schemas"];
+        for schema in [
+            "ResponseBody",
+            "UserRequest",
+            "MediaResponse",
+            "WatchlistResponse",
+        ] {
+            assert!(
+                schemas[schema].is_object(),
+                "{} isn\'t in the spec.",
+                schema
+            );
+        }
+
+        let error = &schemas["ResponseBody"]["properties"];
+        assert!(error["code"].is_object());
+        assert!(error["details"].is_object());
+
+        // The responses of the middlewares are only in the routes they wrap.
+        let responses = &res.body["paths"]["/watchlist"]["get"]["responses"];
+        assert!(responses["401"].is_object());
+        assert!(responses["503"].is_object());
+        let responses = &res.body["paths"]["/user/login"]["post"]["responses"];
+        assert_eq!(
+            responses["401"]["description"],
+            "The username or the password is wrong."
+        );
+        assert!(responses["429"]["headers"]["Retry-After"].is_object());
+        let responses = &res.body["paths"]["/healthz"]["get"]["responses"];
+        assert!(responses["503"].is_null());
+    });
+}
+
+#[cfg(feature = "docs-ui")]
+#[test]
+fn get_docs_serves_the_ui() {
+    run(async {
+        let req = Request::builder()
+            .uri("/docs/")
+            .body(Body::empty())
+            .unwrap();
+        let res = app().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    });
+}
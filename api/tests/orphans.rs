@@ -0,0 +1,102 @@
+// Libs
+use api::database::{cleanup_orphans, DATABASE};
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::*;
+mod common;
+
+// Functions
+/**
+ * A method to delete some record without its cascade, like the old deletes did.
+*/
+async fn delete_raw(table: &str, id: &str) {
+    DATABASE
+        .query("DELETE type::thing($table, $id)")
+        .bind(("table", table))
+        .bind(("id", id))
+        .await
+        .expect("Couldn\'t delete the record.")
+        .check()
+        .expect("Couldn\'t delete the record.");
+}
+
+/**
+ * A method to run the cleanup of the orphans.
+ * The counts of the report aren't checked, since the other tests of the file may have orphans too.
+*/
+async fn cleanup() {
+    cleanup_orphans()
+        .await
+        .expect("Couldn\'t clean up the orphans.");
+}
+
+// Tests
+#[test]
+fn cleanup_orphans_removes_the_watchlists_without_owner() {
+    run(async {
+        let gone = create_user("orphanowner").await;
+        let live = create_user("liveowner").await;
+        let orphan_id = create_watchlist(&gone, "Orphan").await;
+        let live_id = create_watchlist(&live, "Live").await;
+
+        delete_raw("user", &gone.id).await;
+        cleanup().await;
+
+        assert!(!exists("watchlist", &orphan_id).await);
+        assert!(exists("watchlist", &live_id).await);
+    });
+}
+
+#[test]
+fn cleanup_orphans_removes_the_members_without_user() {
+    run(async {
+        let owner = create_user("membersowner").await;
+        let gone = create_user("orphanmember").await;
+        let live = create_user("livemember").await;
+        let watchlist_id = create_watchlist(&owner, "Members").await;
+        add_member(&owner, &watchlist_id, &gone, "viewer").await;
+        add_member(&owner, &watchlist_id, &live, "viewer").await;
+
+        delete_raw("user", &gone.id).await;
+        cleanup().await;
+
+        let res = get(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&owner.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        let members = res.data()["members"].as_array().unwrap();
+        assert_eq!(members.len(), 1, "{}", res.body);
+        assert_eq!(members[0]["user"], live.id.as_str());
+    });
+}
+
+#[test]
+fn cleanup_orphans_removes_the_invitations_without_invitee() {
+    run(async {
+        let owner = create_user("invitesowner").await;
+        let gone = create_user("orphaninvitee").await;
+        let live = create_user("liveinvitee").await;
+        let watchlist_id = create_watchlist(&owner, "Invitations").await;
+
+        let mut invitations = Vec::new();
+        for invitee in [&gone, &live] {
+            let res = post(
+                &format!("/watchlist/{}/invitation", watchlist_id),
+                Some(&owner.access_token),
+                Some(json!({ "username": invitee.username, "role": "viewer" })),
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+            invitations.push(string(&res.data()["id"]));
+        }
+
+        delete_raw("user", &gone.id).await;
+        cleanup().await;
+
+        assert!(!exists("invitation", &invitations[0]).await);
+        assert!(exists("invitation", &invitations[1]).await);
+    });
+}
@@ -0,0 +1,138 @@
+// Libs
+use std::time::Duration;
+
+use api::{
+    limiter::{Limiter, LimiterConfig, MAX_TRACKED_KEYS},
+    router::get_router,
+};
+use axum::http::{header, Method, StatusCode};
+use tower::ServiceExt;
+
+use common::*;
+mod common;
+
+// Functions
+/**
+ * A method to run some test with the rate limit enabled.
+ * The ip bucket is large, since all the requests come from the same mocked address.
+*/
+fn run_limited<F: std::future::Future<Output = ()>>(test: F) {
+    run_with_rate_limit(limited_config(), test);
+}
+
+/**
+ * A method to get the configuration of the limited tests.
+*/
+fn limited_config() -> LimiterConfig {
+    LimiterConfig {
+        enabled: true,
+        ip_burst: 1_000,
+        ip_per_minute: 1_000,
+        account_burst: 10,
+        account_per_minute: 1,
+        login_max_failures: 3,
+        login_lockout_base: Duration::from_secs(30),
+        login_lockout_max: Duration::from_secs(60 * 60),
+    }
+}
+
+/**
+ * A method to check if the response is a 429 with the given code and a Retry-After.
+*/
+fn assert_too_many_requests(res: &TestResponse, code: &str) {
+    assert_eq!(res.status, StatusCode::TOO_MANY_REQUESTS, "{}", res.body);
+    assert_eq!(res.body["code"], code);
+    let retry_after: u64 = res.headers[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after >= 1);
+}
+
+// Tests
+#[test]
+fn repeated_requests_are_rate_limited() {
+    run_limited(async {
+        let user = create_user("ratelimited").await;
+
+        for _ in 0..10 {
+            let res = get("/watchlist", Some(&user.access_token)).await;
+            assert_eq!(res.status, StatusCode::OK);
+        }
+        let res = get("/watchlist", Some(&user.access_token)).await;
+        assert_too_many_requests(&res, "RATE_LIMITED");
+    });
+}
+
+#[test]
+fn the_login_lockout_resets_after_a_successful_login() {
+    run_limited(async {
+        create_user("lockout").await;
+
+        // The failures before a successful login are forgotten.
+        for _ in 0..2 {
+            let res = login("lockout", "wrong-password").await;
+            assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(login("lockout", PASSWORD).await.status, StatusCode::OK);
+        for _ in 0..2 {
+            let res = login("lockout", "wrong-password").await;
+            assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(login("lockout", PASSWORD).await.status, StatusCode::OK);
+
+        // The third failure in a row locks the login, even with the right password.
+        for _ in 0..3 {
+            let res = login("lockout", "wrong-password").await;
+            assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        }
+        let res = login("lockout", PASSWORD).await;
+        assert_too_many_requests(&res, "LOGIN_LOCKED");
+    });
+}
+
+#[test]
+fn the_logins_with_invalid_usernames_are_locked_too() {
+    run_limited(async {
+        for _ in 0..3 {
+            let res = login("x", PASSWORD).await;
+            assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        }
+        let res = login("x", PASSWORD).await;
+        assert_too_many_requests(&res, "LOGIN_LOCKED");
+    });
+}
+
+#[test]
+fn the_ip_is_skipped_without_the_connection_information() {
+    run_limited(async {
+        // Unlike app(), the router has no mocked client address.
+        let req = request(Method::GET, "/watchlist", None, None);
+        let res = get_router()
+            .oneshot(req)
+            .await
+            .expect("Couldn\'t send the request.");
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    });
+}
+
+#[test]
+fn the_oldest_login_failures_are_forgotten_first() {
+    let limiter = Limiter::new(limited_config());
+    for _ in 0..3 {
+        limiter.register_login_failure("locked");
+    }
+    for i in 0..MAX_TRACKED_KEYS - 2 {
+        limiter.register_login_failure(&format!("flood{}", i));
+    }
+    for _ in 0..2 {
+        limiter.register_login_failure("recent");
+    }
+
+    // The map is full, so the next username forgets the oldest ones.
+    limiter.register_login_failure("newcomer");
+    assert!(limiter.check_login("locked").is_err());
+    limiter.register_login_failure("recent");
+    assert!(limiter.check_login("recent").is_err());
+}
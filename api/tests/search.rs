@@ -0,0 +1,84 @@
+// Libs
+use axum::http::StatusCode;
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn get_search_ranks_and_highlights_the_hits() {
+    run(async {
+        let owner = create_user("searchowner").await;
+        let watchlist_id = create_watchlist(&owner, "Matrix trilogy").await;
+        create_media(&owner, &watchlist_id, "The Matrix").await;
+        create_media(&owner, &watchlist_id, "Inception").await;
+
+        let res = get("/search?q=matrix", Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        let watchlists = res.data()["watchlists"].as_array().unwrap();
+        assert_eq!(watchlists.len(), 1);
+        assert_eq!(watchlists[0]["id"], watchlist_id.as_str());
+        let medias = res.data()["medias"].as_array().unwrap();
+        assert_eq!(medias.len(), 1);
+        assert_eq!(medias[0]["title"], "The Matrix");
+        assert_eq!(medias[0]["highlights"]["title"], "The <mark>Matrix</mark>");
+        assert!(medias[0]["score"].is_number());
+    });
+}
+
+#[test]
+fn get_search_escapes_the_highlighted_texts() {
+    run(async {
+        let owner = create_user("searchescape").await;
+        let watchlist_id = create_watchlist(&owner, "Escapes").await;
+        create_media(&owner, &watchlist_id, "<script> Matrix & co").await;
+
+        let res = get("/search?q=matrix", Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        let medias = res.data()["medias"].as_array().unwrap();
+        assert_eq!(medias.len(), 1);
+        assert_eq!(medias[0]["title"], "<script> Matrix & co");
+        assert_eq!(
+            medias[0]["highlights"]["title"],
+            "&lt;script&gt; <mark>Matrix</mark> &amp; co"
+        );
+    });
+}
+
+#[test]
+fn get_search_only_returns_the_accessible_watchlists() {
+    run(async {
+        let owner = create_user("searchscope").await;
+        let stranger = create_user("searchstranger").await;
+        let watchlist_id = create_watchlist(&owner, "Private stuff").await;
+        create_media(&owner, &watchlist_id, "Private media").await;
+
+        let res = get("/search?q=private", Some(&stranger.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(res.data()["watchlists"].as_array().unwrap().is_empty());
+        assert!(res.data()["medias"].as_array().unwrap().is_empty());
+    });
+}
+
+#[test]
+fn get_search_validates_the_parameters() {
+    run(async {
+        let user = create_user("searchparams").await;
+
+        for uri in ["/search?q=", "/search?q=%20%20", "/search?q=a&limit=0"] {
+            let res = get(uri, Some(&user.access_token)).await;
+            assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", uri);
+            assert_eq!(res.body["code"], "INVALID_FIELDS", "{}", uri);
+        }
+        let long = "a".repeat(61);
+        let res = get(&format!("/search?q={}", long), Some(&user.access_token)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+
+        // The query strings that can't be parsed have a typed error too.
+        for uri in ["/search", "/search?q=a&limit=-1"] {
+            let res = get(uri, Some(&user.access_token)).await;
+            assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", uri);
+            assert_eq!(res.body["code"], "INVALID_BODY", "{}", uri);
+        }
+    });
+}
@@ -0,0 +1,89 @@
+// Libs
+use std::time::{Duration, Instant};
+
+use api::shutdown::serve_with_shutdown;
+use axum::{routing::get, Router};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+
+// Functions
+/**
+ * A method to serve a router with a slow route. Returns its address, the shutdown trigger and the server task.
+*/
+async fn serve_slow_route(
+    delay: Duration,
+    deadline: Duration,
+) -> (
+    String,
+    oneshot::Sender<()>,
+    tokio::task::JoinHandle<Result<(), std::io::Error>>,
+) {
+    let router = Router::new().route(
+        "/slow",
+        get(move || async move {
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (sender, receiver) = oneshot::channel();
+    let signal = async {
+        let _ = receiver.await;
+    };
+
+    let server = tokio::spawn(serve_with_shutdown(listener, router, signal, deadline));
+    (address, sender, server)
+}
+
+/**
+ * A method to send a GET request to the slow route. Returns the raw response.
+*/
+async fn get_slow_route(mut stream: TcpStream) -> String {
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response).await;
+    response
+}
+
+// Tests
+#[tokio::test]
+async fn shutdown_drains_the_requests_in_flight() {
+    let (address, sender, server) =
+        serve_slow_route(Duration::from_millis(500), Duration::from_secs(10)).await;
+    let stream = TcpStream::connect(&address).await.unwrap();
+    let request = tokio::spawn(get_slow_route(stream));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    sender.send(()).unwrap();
+
+    let response = request.await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("done"));
+    server.await.unwrap().unwrap();
+
+    // The listener is closed after the shutdown.
+    assert!(TcpStream::connect(&address).await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_closes_the_connections_after_the_deadline() {
+    let (address, sender, server) =
+        serve_slow_route(Duration::from_secs(60), Duration::from_millis(200)).await;
+    let stream = TcpStream::connect(&address).await.unwrap();
+    let request = tokio::spawn(get_slow_route(stream));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let start = Instant::now();
+    sender.send(()).unwrap();
+
+    server.await.unwrap().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    request.abort();
+}
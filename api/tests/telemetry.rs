@@ -0,0 +1,114 @@
+// Libs
+use std::sync::{Arc, Mutex};
+
+use api::{
+    logger::{initialize_logger, LogConfig},
+    telemetry::{shutdown_telemetry, TelemetryConfig},
+};
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, Request},
+    routing::post,
+    Router,
+};
+use opentelemetry_proto::tonic::{
+    collector::trace::v1::ExportTraceServiceRequest,
+    trace::v1::{span::SpanKind, Span},
+};
+use prost::Message;
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+
+use common::*;
+mod common;
+
+// Data
+static TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+static PARENT_SPAN_ID: &str = "00f067aa0ba902b7";
+
+// Types
+type ReceivedSpans = Arc<Mutex<Vec<Span>>>;
+
+// Functions
+/**
+ * A method to start a stand-in for the OTLP collector. Returns its address and the spans it receives.
+*/
+async fn start_collector() -> (String, ReceivedSpans) {
+    let spans = ReceivedSpans::default();
+    let router = Router::new()
+        .route(
+            "/v1/traces",
+            post(
+                |State(spans): State<ReceivedSpans>, body: Bytes| async move {
+                    let request = ExportTraceServiceRequest::decode(body)
+                        .expect("The collector received an invalid export.");
+                    let mut spans = spans.lock().unwrap();
+                    for resource in request.resource_spans {
+                        for scope in resource.scope_spans {
+                            spans.extend(scope.spans);
+                        }
+                    }
+                },
+            ),
+        )
+        .with_state(spans.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (address, spans)
+}
+
+// Tests
+#[test]
+fn spans_are_exported_in_the_trace_of_the_client() {
+    run(async {
+        let (endpoint, spans) = start_collector().await;
+        let telemetry = TelemetryConfig {
+            otlp_endpoint: Some(endpoint),
+            ..TelemetryConfig::default()
+        };
+        initialize_logger(&LogConfig::default(), &telemetry).unwrap();
+
+        let user = create_user("otelowner").await;
+        let req = Request::builder()
+            .uri("/watchlist")
+            .header(header::ACCEPT, "application/json")
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", user.access_token),
+            )
+            .header(
+                "traceparent",
+                format!("00-{}-{}-01", TRACE_ID, PARENT_SPAN_ID),
+            )
+            .body(Body::empty())
+            .unwrap();
+        app().oneshot(req).await.unwrap();
+
+        // Export the buffered spans.
+        tokio::task::spawn_blocking(shutdown_telemetry)
+            .await
+            .unwrap();
+
+        let spans = spans.lock().unwrap();
+        let request_span = spans
+            .iter()
+            .find(|span| span.name == "GET /watchlist" && hex::encode(&span.trace_id) == TRACE_ID)
+            .expect("The request span wasn\'t exported in the trace of the client.");
+        assert_eq!(hex::encode(&request_span.parent_span_id), PARENT_SPAN_ID);
+        assert_eq!(request_span.kind, SpanKind::Server as i32);
+
+        // The queries of the models are children of the request.
+        let query_span = spans
+            .iter()
+            .find(|span| {
+                span.name == "user.get_watchlists_as_owner"
+                    && span.trace_id == request_span.trace_id
+            })
+            .expect("The query span wasn\'t exported.");
+        assert_eq!(query_span.parent_span_id, request_span.span_id);
+        assert_eq!(query_span.kind, SpanKind::Client as i32);
+    });
+}
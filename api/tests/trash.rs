@@ -0,0 +1,170 @@
+// Libs
+use api::{database::DATABASE, models::trash_model::Trash};
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use surrealdb::sql::Datetime;
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn get_trash_returns_the_trashed_watchlists_and_media() {
+    run(async {
+        let owner = create_user("trlistowner").await;
+        let admin = create_user("trlistadmin").await;
+        let trashed_id = create_watchlist(&owner, "Trashed").await;
+        let watchlist_id = create_watchlist(&owner, "Kept").await;
+        add_member(&owner, &watchlist_id, &admin, "admin").await;
+        let media_id = create_media(&owner, &watchlist_id, "Alien").await;
+        create_media(&owner, &watchlist_id, "Aliens").await;
+
+        delete(
+            &format!("/watchlist/{}", trashed_id),
+            Some(&owner.access_token),
+        )
+        .await;
+        delete(&format!("/media/{}", media_id), Some(&owner.access_token)).await;
+
+        let res = get("/trash", Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["watchlists"].as_array().unwrap().len(), 1);
+        assert_eq!(res.data()["watchlists"][0]["id"], trashed_id.as_str());
+        assert!(res.data()["watchlists"][0]["deleted_at"].is_string());
+        assert_eq!(res.data()["medias"].as_array().unwrap().len(), 1);
+        assert_eq!(res.data()["medias"][0]["id"], media_id.as_str());
+
+        // The admin manages the media, but doesn't own the watchlist.
+        let res = get("/trash", Some(&admin.access_token)).await;
+        assert_eq!(res.data()["watchlists"], json!([]));
+        assert_eq!(res.data()["medias"].as_array().unwrap().len(), 1);
+    });
+}
+
+#[test]
+fn post_trash_restore_restores_a_watchlist() {
+    run(async {
+        let owner = create_user("trwlowner").await;
+        let admin = create_user("trwladmin").await;
+        let watchlist_id = create_watchlist(&owner, "Restored").await;
+        add_member(&owner, &watchlist_id, &admin, "admin").await;
+        delete(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&owner.access_token),
+        )
+        .await;
+        let uri = format!("/trash/{}/restore", watchlist_id);
+
+        let res = post(&uri, Some(&admin.access_token), None).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = post(&uri, Some(&owner.access_token), None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(res.data().get("deleted_at").is_none());
+
+        let res = get(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&admin.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+        let res = post(&uri, Some(&owner.access_token), None).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+    });
+}
+
+#[test]
+fn post_trash_restore_restores_a_media() {
+    run(async {
+        let owner = create_user("trmdowner").await;
+        let editor = create_user("trmdeditor").await;
+        let watchlist_id = create_watchlist(&owner, "Restored").await;
+        add_member(&owner, &watchlist_id, &editor, "editor").await;
+        let media_id = create_media(&owner, &watchlist_id, "Alien").await;
+        delete(&format!("/media/{}", media_id), Some(&owner.access_token)).await;
+        let uri = format!("/trash/{}/restore", media_id);
+
+        let res = post(&uri, Some(&editor.access_token), None).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        // The media can't be restored while its watchlist is trashed.
+        let watchlist_uri = format!("/watchlist/{}", watchlist_id);
+        delete(&watchlist_uri, Some(&owner.access_token)).await;
+        let res = post(&uri, Some(&owner.access_token), None).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        post(
+            &format!("/trash/{}/restore", watchlist_id),
+            Some(&owner.access_token),
+            None,
+        )
+        .await;
+
+        let res = post(&uri, Some(&owner.access_token), None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        let res = get(&format!("/media/{}", media_id), Some(&editor.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+    });
+}
+
+#[test]
+fn purge_only_deletes_what_was_trashed_before_the_retention() {
+    run(async {
+        let owner = create_user("trpurgeowner").await;
+        let mut trashed = Vec::new();
+        for title in ["Expired", "Recent"] {
+            let watchlist_id = create_watchlist(&owner, title).await;
+            let res = post(
+                "/media",
+                Some(&owner.access_token),
+                Some(json!({
+                    "title": "Dark",
+                    "description": "Some series",
+                    "watchlist": watchlist_id,
+                    "watched": false,
+                    "kind": "series",
+                })),
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+            let media_id = string(&res.data()["id"]);
+            let res = post(
+                &format!("/media/{}/episode", media_id),
+                Some(&owner.access_token),
+                Some(json!({ "season": 1, "number": 1 })),
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+            let episode_id = string(&res.data()["id"]);
+
+            delete(
+                &format!("/watchlist/{}", watchlist_id),
+                Some(&owner.access_token),
+            )
+            .await;
+            trashed.push((watchlist_id, media_id, episode_id));
+        }
+
+        // The first watchlist was trashed before the retention of 30 days.
+        DATABASE
+            .query("UPDATE type::thing('watchlist', $id) SET deleted_at = time::now() - 40d")
+            .bind(("id", &trashed[0].0))
+            .await
+            .expect("Couldn\'t update the watchlist.")
+            .check()
+            .expect("Couldn\'t update the watchlist.");
+
+        Trash::purge(Datetime::from(Utc::now() - Duration::days(30)))
+            .await
+            .expect("Couldn\'t purge the trash.");
+
+        let (watchlist_id, media_id, episode_id) = &trashed[0];
+        assert!(!exists("watchlist", watchlist_id).await);
+        assert!(!exists("media", media_id).await);
+        assert!(!exists("episode", episode_id).await);
+        let (watchlist_id, media_id, episode_id) = &trashed[1];
+        assert!(exists("watchlist", watchlist_id).await);
+        assert!(exists("media", media_id).await);
+        assert!(exists("episode", episode_id).await);
+    });
+}
@@ -0,0 +1,278 @@
+// Libs
+use api::database::DATABASE;
+use axum::http::{header, Method, StatusCode};
+use ring::digest::{digest, SHA512};
+use serde_json::json;
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn post_user_creates_an_user() {
+    run(async {
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "signup", "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED);
+        assert_eq!(res.body["status"], "Success");
+        assert_eq!(res.data()["username"], "signup");
+        assert!(res.data().get("password").is_none());
+    });
+}
+
+#[test]
+fn post_user_rejects_invalid_and_duplicated_usernames() {
+    run(async {
+        create_user("duplicated").await;
+
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "duplicated", "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["status"], "Failed");
+
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "x", "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    });
+}
+
+#[test]
+fn post_user_login_returns_a_token_pair() {
+    run(async {
+        let user = create_user("login").await;
+
+        let res = login("login", PASSWORD).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["user"]["id"], user.id.as_str());
+        assert_eq!(res.data()["token_type"], "Bearer");
+        assert!(res.data()["access_token"].is_string());
+        assert!(res.data()["refresh_token"].is_string());
+
+        let res = login("login", "wrong-password").await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        let res = login("nobody", PASSWORD).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+
+        // An invalid username fails like an unknown one.
+        let res = login("x", PASSWORD).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_CREDENTIALS");
+    });
+}
+
+#[test]
+fn post_user_login_rehashes_the_legacy_passwords() {
+    run(async {
+        // The users created before Argon2 have an unsalted SHA512 hash.
+        let legacy_hash = hex::encode(digest(&SHA512, PASSWORD.as_bytes()));
+        DATABASE
+            .query("CREATE user CONTENT { username: 'legacy', username_key: 'legacy', password: $password, created_at: time::now(), updated_at: time::now() }")
+            .bind(("password", &legacy_hash))
+            .await
+            .expect("Couldn\'t create the legacy user.")
+            .check()
+            .expect("Couldn\'t create the legacy user.");
+
+        let res = login("legacy", "wrong-password").await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        let res = login("legacy", PASSWORD).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+        let password: Option<String> = DATABASE
+            .query("SELECT VALUE password FROM user WHERE username_key = 'legacy'")
+            .await
+            .expect("Couldn\'t get the password.")
+            .take(0)
+            .expect("Couldn\'t get the password.");
+        assert!(password.unwrap().starts_with("$argon2id$"));
+        assert_eq!(login("legacy", PASSWORD).await.status, StatusCode::OK);
+    });
+}
+
+#[test]
+fn post_user_refresh_only_accepts_refresh_tokens() {
+    run(async {
+        let user = create_user("refresh").await;
+
+        let res = post("/user/refresh", Some(&user.refresh_token), None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["user"]["id"], user.id.as_str());
+
+        let res = post("/user/refresh", Some(&user.access_token), None).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    });
+}
+
+#[test]
+fn post_user_login_rejects_the_missing_or_malformed_credentials() {
+    run(async {
+        let res = post("/user/login", None, None).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_CREDENTIALS");
+
+        let mut req = request(Method::POST, "/user/login", None, None);
+        req.headers_mut()
+            .insert(header::AUTHORIZATION, "Basic %%%".parse().unwrap());
+        let res = send(req).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_CREDENTIALS");
+    });
+}
+
+#[test]
+fn post_user_refresh_rejects_the_missing_or_malformed_tokens() {
+    run(async {
+        let res = post("/user/refresh", None, None).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_TOKEN");
+
+        let mut req = request(Method::POST, "/user/refresh", None, None);
+        req.headers_mut()
+            .insert(header::AUTHORIZATION, "Garbage".parse().unwrap());
+        let res = send(req).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_TOKEN");
+    });
+}
+
+#[test]
+fn get_user_returns_the_user() {
+    run(async {
+        let user = create_user("getuser").await;
+
+        let res = get(&format!("/user/{}", user.id), None).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["username"], "getuser");
+    });
+}
+
+#[test]
+fn patch_user_updates_the_logged_user() {
+    run(async {
+        let user = create_user("patchuser").await;
+        create_user("patchtaken").await;
+
+        let res = patch(
+            "/user",
+            Some(&user.access_token),
+            json!({ "username": "patchtaken", "password": PASSWORD }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+
+        let res = patch(
+            "/user",
+            Some(&user.access_token),
+            json!({ "username": "patched", "password": "new-password" }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["username"], "patched");
+        assert_eq!(
+            login("patched", "new-password").await.status,
+            StatusCode::OK
+        );
+    });
+}
+
+#[test]
+fn delete_user_removes_the_user_and_its_watchlists() {
+    run(async {
+        let user = create_user("deleteuser").await;
+        let watchlist_id = create_watchlist(&user, "Owned by the user").await;
+
+        let res = delete("/user", Some(&user.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+
+        assert_eq!(
+            login("deleteuser", PASSWORD).await.status,
+            StatusCode::UNAUTHORIZED
+        );
+        let other = create_user("deleteother").await;
+        let res = get(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&other.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+
+        // The tokens of the deleted user are revoked.
+        let res = post(
+            "/watchlist",
+            Some(&user.access_token),
+            Some(json!({ "title": "After delete", "description": "Some description" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_TOKEN");
+        let res = post("/user/refresh", Some(&user.refresh_token), None).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    });
+}
+
+#[test]
+fn a_new_password_revokes_the_tokens() {
+    run(async {
+        let user = create_user("revokeuser").await;
+
+        // Keeping the password keeps the tokens.
+        let res = patch(
+            "/user",
+            Some(&user.access_token),
+            json!({ "username": "revokeduser", "password": PASSWORD }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(
+            get("/watchlist", Some(&user.access_token)).await.status,
+            StatusCode::OK
+        );
+
+        let res = patch(
+            "/user",
+            Some(&user.access_token),
+            json!({ "username": "revokeduser", "password": "new-password" }),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+
+        let res = get("/watchlist", Some(&user.access_token)).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_TOKEN");
+        let res = post("/user/refresh", Some(&user.refresh_token), None).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["code"], "INVALID_TOKEN");
+
+        // The new tokens work.
+        let res = login("revokeduser", "new-password").await;
+        assert_eq!(res.status, StatusCode::OK);
+        let access_token = res.data()["access_token"].as_str().unwrap().to_owned();
+        assert_eq!(
+            get("/watchlist", Some(&access_token)).await.status,
+            StatusCode::OK
+        );
+    });
+}
+
+#[test]
+fn routes_with_bearer_reject_missing_and_invalid_tokens() {
+    run(async {
+        let res = get("/watchlist", None).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+
+        let res = get("/watchlist", Some("not-a-token")).await;
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    });
+}
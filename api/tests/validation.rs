@@ -0,0 +1,156 @@
+// Libs
+use api::migrations::MIGRATIONS;
+use api::validation::conditions;
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::*;
+mod common;
+
+// Functions
+/**
+ * A method to get the invalid fields of some failed response.
+*/
+fn invalid_fields(body: &Value) -> Vec<&str> {
+    body["details"]
+        .as_array()
+        .expect("The details are missing.")
+        .iter()
+        .map(|detail| detail["field"].as_str().unwrap())
+        .collect()
+}
+
+// Tests
+#[test]
+fn post_user_reports_every_invalid_field() {
+    run(async {
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "x", "password": "short" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_FIELDS");
+        assert_eq!(invalid_fields(&res.body), ["username", "password"]);
+    });
+}
+
+#[test]
+fn post_media_is_validated_before_the_watchlist() {
+    run(async {
+        let user = create_user("validmedia").await;
+
+        let res = post(
+            "/media",
+            Some(&user.access_token),
+            Some(json!({ "title": "", "description": "é", "watchlist": "", "watched": false })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            invalid_fields(&res.body),
+            ["title", "description", "watchlist"]
+        );
+
+        let res = post(
+            "/media",
+            Some(&user.access_token),
+            Some(json!({ "title": "The Matrix" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_BODY");
+    });
+}
+
+#[test]
+fn the_titles_accept_any_language() {
+    run(async {
+        let user = create_user("unicodetitle").await;
+
+        // The decomposed "é" is stored composed.
+        let res = post(
+            "/watchlist",
+            Some(&user.access_token),
+            Some(json!({ "title": "Ame\u{301}lie", "description": "千と千尋の神隠し" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+        assert_eq!(res.data()["title"], "Amélie");
+        assert_eq!(res.data()["description"], "千と千尋の神隠し");
+
+        // The lengths are in graphemes, whatever the number of characters.
+        let title = "e\u{301}".repeat(20);
+        let res = post(
+            "/watchlist",
+            Some(&user.access_token),
+            Some(json!({ "title": title, "description": "Some description" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+
+        let res = post(
+            "/watchlist",
+            Some(&user.access_token),
+            Some(json!({ "title": "é".repeat(21), "description": "🎬🎬🎬" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(invalid_fields(&res.body), ["title", "description"]);
+    });
+}
+
+#[test]
+fn the_usernames_are_unique_by_their_case_folding() {
+    run(async {
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "Straße", "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+        assert_eq!(res.data()["username"], "Straße");
+
+        let res = post(
+            "/user",
+            None,
+            Some(json!({ "username": "STRASSE", "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "USERNAME_TAKEN");
+
+        let res = login("strasse", PASSWORD).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["user"]["username"], "Straße");
+    });
+}
+
+#[test]
+fn the_migrations_follow_the_current_rules() {
+    // The versions follow each other, so none is skipped after some renumbering.
+    for (i, migration) in MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.version as usize, i + 1, "{}", migration.name);
+    }
+    for migration in MIGRATIONS {
+        assert!(
+            surrealdb::sql::parse(migration.query).is_ok(),
+            "The migration {} is invalid.",
+            migration.version
+        );
+    }
+
+    // A changed rule is only applied by a new migration redefining its fields.
+    // The conditions end their statement, so "<= 24" isn't found in "<= 240".
+    for (name, condition) in conditions() {
+        let statement_end = format!("{};", condition);
+        assert!(
+            MIGRATIONS.iter().any(|m| m.query.contains(&statement_end)),
+            "The rule {} changed. Add a migration redefining its fields with: {}",
+            name,
+            condition
+        );
+    }
+}
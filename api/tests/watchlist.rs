@@ -0,0 +1,305 @@
+// Libs
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::*;
+mod common;
+
+// Tests
+#[test]
+fn post_watchlist_creates_a_watchlist() {
+    run(async {
+        let owner = create_user("wlcreate").await;
+
+        let res = post(
+            "/watchlist",
+            Some(&owner.access_token),
+            Some(json!({ "title": "Movies", "description": "Some movies" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::CREATED);
+        assert_eq!(res.data()["owner"], owner.id.as_str());
+        assert_eq!(res.data()["title"], "Movies");
+        assert_eq!(res.data()["members"], json!([]));
+
+        let res = post(
+            "/watchlist",
+            Some(&owner.access_token),
+            Some(json!({ "title": "x", "description": "Some movies" })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    });
+}
+
+#[test]
+fn get_watchlists_returns_owned_and_joined_watchlists() {
+    run(async {
+        let owner = create_user("wllistowner").await;
+        let member = create_user("wllistmember").await;
+        let owned = create_watchlist(&member, "Owned").await;
+        let joined = create_watchlist(&owner, "Joined").await;
+        create_watchlist(&owner, "Not joined").await;
+        add_member(&owner, &joined, &member, "viewer").await;
+
+        let res = get("/watchlist", Some(&member.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+        let mut ids: Vec<String> = res
+            .data()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| string(&w["id"]))
+            .collect();
+        ids.sort();
+        let mut expected = vec![owned, joined];
+        expected.sort();
+        assert_eq!(ids, expected);
+    });
+}
+
+#[test]
+fn get_watchlist_is_only_allowed_to_members() {
+    run(async {
+        let owner = create_user("wlgetowner").await;
+        let viewer = create_user("wlgetviewer").await;
+        let stranger = create_user("wlgetstranger").await;
+        let watchlist_id = create_watchlist(&owner, "Shared").await;
+        add_member(&owner, &watchlist_id, &viewer, "viewer").await;
+        let uri = format!("/watchlist/{}", watchlist_id);
+
+        for user in [&owner, &viewer] {
+            let res = get(&uri, Some(&user.access_token)).await;
+            assert_eq!(res.status, StatusCode::OK);
+            assert_eq!(res.data()["id"], watchlist_id.as_str());
+        }
+
+        let res = get(&uri, Some(&stranger.access_token)).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = get("/watchlist/unknown", Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+    });
+}
+
+#[test]
+fn patch_watchlist_requires_an_admin() {
+    run(async {
+        let owner = create_user("wlpatchowner").await;
+        let admin = create_user("wlpatchadmin").await;
+        let editor = create_user("wlpatcheditor").await;
+        let watchlist_id = create_watchlist(&owner, "Before").await;
+        add_member(&owner, &watchlist_id, &admin, "admin").await;
+        add_member(&owner, &watchlist_id, &editor, "editor").await;
+        let uri = format!("/watchlist/{}", watchlist_id);
+        let members = json!([
+            { "user": admin.id, "role": "admin" },
+            { "user": editor.id, "role": "editor" },
+        ]);
+
+        let body = json!({ "title": "After", "description": "Updated", "members": members });
+        let res = patch(&uri, Some(&editor.access_token), body.clone()).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = patch(&uri, Some(&admin.access_token), body).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["title"], "After");
+        assert_eq!(res.data()["owner"], owner.id.as_str());
+
+        // Only the owner can remove an admin.
+        let body = json!({
+            "title": "After",
+            "description": "Updated",
+            "members": [{ "user": editor.id, "role": "editor" }],
+        });
+        let res = patch(&uri, Some(&admin.access_token), body.clone()).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+        let res = patch(&uri, Some(&owner.access_token), body).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["members"].as_array().unwrap().len(), 1);
+    });
+}
+
+#[test]
+fn patch_watchlist_without_members_keeps_them() {
+    run(async {
+        let owner = create_user("wlkeepowner").await;
+        let admin = create_user("wlkeepadmin").await;
+        let editor = create_user("wlkeepeditor").await;
+        let watchlist_id = create_watchlist(&owner, "Before").await;
+        add_member(&owner, &watchlist_id, &admin, "admin").await;
+        add_member(&owner, &watchlist_id, &editor, "editor").await;
+        let uri = format!("/watchlist/{}", watchlist_id);
+        let members = get(&uri, Some(&owner.access_token)).await.data()["members"].clone();
+        assert_eq!(members.as_array().unwrap().len(), 2);
+
+        for user in [&owner, &admin] {
+            let body = json!({ "title": "After", "description": "Updated" });
+            let res = patch(&uri, Some(&user.access_token), body).await;
+            assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+            assert_eq!(res.data()["title"], "After");
+            assert_eq!(res.data()["members"], members);
+        }
+        let res = get(&uri, Some(&owner.access_token)).await;
+        assert_eq!(res.data()["members"], members);
+    });
+}
+
+#[test]
+fn delete_watchlist_is_only_allowed_to_the_owner() {
+    run(async {
+        let owner = create_user("wldeleteowner").await;
+        let admin = create_user("wldeleteadmin").await;
+        let watchlist_id = create_watchlist(&owner, "Deleted").await;
+        add_member(&owner, &watchlist_id, &admin, "admin").await;
+        let uri = format!("/watchlist/{}", watchlist_id);
+
+        let res = delete(&uri, Some(&admin.access_token)).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = delete(&uri, Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::OK);
+
+        let res = get(&uri, Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND);
+        let res = get("/watchlist", Some(&admin.access_token)).await;
+        assert_eq!(res.data(), &json!([]));
+    });
+}
+
+#[test]
+fn post_watchlist_leave_removes_the_member() {
+    run(async {
+        let owner = create_user("wlleaveowner").await;
+        let member = create_user("wlleavemember").await;
+        let watchlist_id = create_watchlist(&owner, "Left").await;
+        add_member(&owner, &watchlist_id, &member, "editor").await;
+        let uri = format!("/watchlist/{}/leave", watchlist_id);
+
+        let res = post(&uri, Some(&owner.access_token), None).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+
+        let res = post(&uri, Some(&member.access_token), None).await;
+        assert_eq!(res.status, StatusCode::OK);
+
+        let res = post(&uri, Some(&member.access_token), None).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+        let res = get(
+            &format!("/watchlist/{}", watchlist_id),
+            Some(&member.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+    });
+}
+
+#[test]
+fn post_watchlist_transfer_changes_the_owner() {
+    run(async {
+        let owner = create_user("wltransowner").await;
+        let member = create_user("wltransmember").await;
+        let stranger = create_user("wltransstranger").await;
+        let watchlist_id = create_watchlist(&owner, "Transferred").await;
+        add_member(&owner, &watchlist_id, &member, "viewer").await;
+        let uri = format!("/watchlist/{}/transfer", watchlist_id);
+
+        let res = post(
+            &uri,
+            Some(&member.access_token),
+            Some(json!({ "new_owner": member.id })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "new_owner": stranger.id })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+
+        let res = post(
+            &uri,
+            Some(&owner.access_token),
+            Some(json!({ "new_owner": member.id })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.data()["owner"], member.id.as_str());
+        assert_eq!(
+            res.data()["members"],
+            json!([{ "user": owner.id, "role": "admin" }])
+        );
+    });
+}
+
+#[test]
+fn post_watchlist_transfer_rejects_the_invalid_bodies() {
+    run(async {
+        let owner = create_user("wltransbody").await;
+        let watchlist_id = create_watchlist(&owner, "Transferred").await;
+
+        let res = post(
+            &format!("/watchlist/{}/transfer", watchlist_id),
+            Some(&owner.access_token),
+            Some(json!({ "owner": owner.id })),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_BODY");
+    });
+}
+
+#[test]
+fn get_watchlist_medias_paginates_the_media() {
+    run(async {
+        let owner = create_user("wlmediaowner").await;
+        let stranger = create_user("wlmediastranger").await;
+        let watchlist_id = create_watchlist(&owner, "Paginated").await;
+        for title in ["Delta", "alpha", "Charlie", "bravo", "Echo"] {
+            create_media(&owner, &watchlist_id, title).await;
+        }
+        let uri = format!("/watchlist/{}/media", watchlist_id);
+
+        // Walk through the pages.
+        let mut titles = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page_uri = match &cursor {
+                None => format!("{}?sort=title&limit=2", uri),
+                Some(cursor) => format!("{}?sort=title&limit=2&cursor={}", uri, cursor),
+            };
+            let res = get(&page_uri, Some(&owner.access_token)).await;
+            assert_eq!(res.status, StatusCode::OK);
+            assert_eq!(res.data()["total"], 5);
+            titles.extend(
+                res.data()["items"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|m| string(&m["title"])),
+            );
+            match &res.data()["next_cursor"] {
+                Value::Null => break,
+                next => cursor = Some(string(next)),
+            }
+        }
+        assert_eq!(titles, ["alpha", "bravo", "Charlie", "Delta", "Echo"]);
+
+        let res = get(&format!("{}?limit=0", uri), Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_FIELDS");
+        let res = get(&format!("{}?limit=many", uri), Some(&owner.access_token)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["code"], "INVALID_BODY");
+        let res = get(
+            &format!("{}?cursor=invalid", uri),
+            Some(&owner.access_token),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        let res = get(&uri, Some(&stranger.access_token)).await;
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+    });
+}